@@ -5,6 +5,7 @@ use nekosbest::{SearchQuery, SearchQueryKind};
 async fn main() {
     let client = nekosbest::client::Client::new(ClientConfig {
         search_ratelimit_behavior: nekosbest::client::SearchRatelimitBehavior::Error,
+        ..Default::default()
     });
 
     for i in 0..100 {
@@ -0,0 +1,111 @@
+//! An abstraction over the API, so that code using it can be tested
+//! against a fake implementation, like [`MockApi`](crate::mock::MockApi)
+//! (with the `mock` feature).
+
+#[cfg(feature = "blocking")]
+use nb_blocking_util::blocking;
+
+use crate::client::Client;
+use crate::details::Details;
+use crate::{AnyCategory, NekosBestError, NekosBestResponse, NekosBestResponseSingle, SearchQuery};
+
+macro_rules! api_trait {
+    ($(
+        $(#[$at:meta])*
+        fn $name:ident(&self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;
+    )*) => {
+        /// The operations of the API, implemented by [`Client`].
+        ///
+        /// Depend on this trait instead of [`Client`] to be able to
+        /// inject a fake implementation in tests.
+        #[cfg(not(feature = "blocking"))]
+        #[async_trait::async_trait]
+        pub trait NekosBestApi: Send + Sync {
+            $(
+                $(#[$at])*
+                async fn $name(&self $(, $arg: $ty)*) -> Result<$ret, NekosBestError>;
+            )*
+        }
+
+        /// The operations of the API, implemented by [`Client`].
+        ///
+        /// Depend on this trait instead of [`Client`] to be able to
+        /// inject a fake implementation in tests.
+        #[cfg(feature = "blocking")]
+        pub trait NekosBestApi: Send + Sync {
+            $(
+                $(#[$at])*
+                fn $name(&self $(, $arg: $ty)*) -> Result<$ret, NekosBestError>;
+            )*
+        }
+    };
+}
+
+api_trait! {
+    /// Gets a single image. Takes an [`AnyCategory`] rather than an
+    /// `impl Into<AnyCategory>` to keep the trait object safe, so call it
+    /// with `Category::Hug.into()`.
+    fn get(&self, category: AnyCategory) -> NekosBestResponseSingle;
+
+    /// Gets `amount` images.
+    fn get_amount(&self, category: AnyCategory, amount: u8) -> NekosBestResponse;
+
+    /// Searches.
+    fn search(&self, query: SearchQuery) -> NekosBestResponse;
+
+    /// Gets the details of an image or a gif.
+    fn details(&self, url: &str) -> Details;
+
+    /// Gets the metrics.
+    #[cfg(feature = "metrics")]
+    fn metrics(&self) -> crate::metrics::Metrics;
+
+    /// Downloads the image from the given response.
+    #[cfg(feature = "download")]
+    fn download(
+        &self,
+        response: &NekosBestResponseSingle,
+    ) -> crate::download::DownloadResult;
+}
+
+#[cfg_attr(not(feature = "blocking"), async_trait::async_trait)]
+impl NekosBestApi for Client {
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn get(&self, category: AnyCategory) -> Result<NekosBestResponseSingle, NekosBestError> {
+        Client::get(self, category).await
+    }
+
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn get_amount(
+        &self,
+        category: AnyCategory,
+        amount: u8,
+    ) -> Result<NekosBestResponse, NekosBestError> {
+        Client::get_many(self, category, amount).await
+    }
+
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn search(&self, query: SearchQuery) -> Result<NekosBestResponse, NekosBestError> {
+        Client::search(self, query).await
+    }
+
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn details(&self, url: &str) -> Result<Details, NekosBestError> {
+        Client::details(self, url).await
+    }
+
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn metrics(&self) -> Result<crate::metrics::Metrics, NekosBestError> {
+        Client::metrics(self).await
+    }
+
+    #[cfg(feature = "download")]
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn download(
+        &self,
+        response: &NekosBestResponseSingle,
+    ) -> Result<crate::download::DownloadResult, NekosBestError> {
+        Client::download(self, response).await
+    }
+}
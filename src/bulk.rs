@@ -0,0 +1,250 @@
+//! Amounts of images per request, and fetching any number of images at once.
+//!
+//! The server clamps the amount of a single request to
+//! `1..=`[`Amount::MAX`], so [`get_bulk`] splits bigger counts into
+//! several requests and drops duplicate urls.
+
+use std::collections::HashSet;
+use std::num::NonZeroU8;
+
+#[cfg(feature = "blocking")]
+use nb_blocking_util::blocking;
+
+use crate::client::{default_client, Client};
+use crate::{AnyCategory, NekosBestError, NekosBestResponse, NekosBestResponseSingle};
+
+/// How many times [`get_bulk`] asks again for the images it is
+/// missing because of duplicates, before giving up.
+const MAX_EXTRA_ROUNDS: usize = 3;
+
+/// The most images [`get_bulk`] gets at once, in 50 requests.
+pub const MAX_BULK_COUNT: usize = 1000;
+
+/// How many requests [`get_bulk`] has in flight at once.
+#[cfg(not(feature = "blocking"))]
+const MAX_CONCURRENT_PAGES: usize = 4;
+
+/// An amount of images the server accepts in a single request,
+/// in `1..=`[`Amount::MAX`].
+///
+/// Can be passed to [`get_amount`](crate::get_amount) and the like, which
+/// also take a plain `u8` and check it at runtime, failing with
+/// [`NekosBestError::InvalidAmount`] when it is out of range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(NonZeroU8);
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("invalid amount {0}, must be in 1..={max}", max = Amount::MAX)]
+pub struct InvalidAmount(pub usize);
+
+impl Amount {
+    /// The maximum amount of images per request.
+    pub const MAX: u8 = 20;
+
+    pub const fn new(amount: u8) -> Option<Self> {
+        match NonZeroU8::new(amount) {
+            Some(amount) if amount.get() <= Self::MAX => Some(Self(amount)),
+            _ => None,
+        }
+    }
+
+    pub const fn get(self) -> u8 {
+        self.0.get()
+    }
+}
+
+impl TryFrom<u8> for Amount {
+    type Error = InvalidAmount;
+
+    fn try_from(amount: u8) -> Result<Self, Self::Error> {
+        Self::new(amount).ok_or(InvalidAmount(amount.into()))
+    }
+}
+
+impl TryFrom<usize> for Amount {
+    type Error = InvalidAmount;
+
+    fn try_from(amount: usize) -> Result<Self, Self::Error> {
+        u8::try_from(amount)
+            .ok()
+            .and_then(Self::new)
+            .ok_or(InvalidAmount(amount))
+    }
+}
+
+impl From<Amount> for u8 {
+    fn from(amount: Amount) -> Self {
+        amount.get()
+    }
+}
+
+/// The result of [`get_bulk`].
+#[derive(Debug)]
+pub struct BulkResponse {
+    /// The images, without duplicate urls, at most [`requested`](Self::requested).
+    pub results: Vec<NekosBestResponseSingle>,
+    pub requested: usize,
+    /// The error that stopped the fetching early, if any.
+    pub error: Option<NekosBestError>,
+}
+
+impl BulkResponse {
+    /// Returns `true` if all the requested images were fetched.
+    pub fn is_complete(&self) -> bool {
+        self.results.len() == self.requested
+    }
+}
+
+/// The amounts of the requests needed to get `count` images.
+fn pages(count: usize) -> impl Iterator<Item = u8> {
+    let max = usize::from(Amount::MAX);
+    (0..count.div_ceil(max)).map(move |page| (count - page * max).min(max) as u8)
+}
+
+/// Gets `pages`, handing them to `on_page` as they arrive. Stops at the
+/// first error, without sending the requests of the pages left.
+#[cfg(not(feature = "blocking"))]
+async fn get_pages(
+    client: &Client,
+    category: &AnyCategory,
+    pages: impl Iterator<Item = u8>,
+    mut on_page: impl FnMut(NekosBestResponse),
+) -> Option<NekosBestError> {
+    use futures::StreamExt;
+
+    let mut pages = futures::stream::iter(pages)
+        .map(|amount| crate::get_with_client_amount(client, category.clone(), amount))
+        .buffered(MAX_CONCURRENT_PAGES);
+    while let Some(page) = pages.next().await {
+        match page {
+            Ok(resp) => on_page(resp),
+            Err(e) => return Some(e),
+        }
+    }
+
+    None
+}
+
+/// Gets `pages`, handing them to `on_page` as they arrive. Stops at the
+/// first error, without sending the requests of the pages left.
+#[cfg(feature = "blocking")]
+fn get_pages(
+    client: &Client,
+    category: &AnyCategory,
+    pages: impl Iterator<Item = u8>,
+    mut on_page: impl FnMut(NekosBestResponse),
+) -> Option<NekosBestError> {
+    for amount in pages {
+        match crate::get_with_client_amount(client, category.clone(), amount) {
+            Ok(resp) => on_page(resp),
+            Err(e) => return Some(e),
+        }
+    }
+
+    None
+}
+
+/// Gets `count` images, with a supplied client, in as many requests
+/// as needed. Duplicate urls are dropped, and asked for again a few times.
+///
+/// The requests are sent concurrently, a few at a time, or one after
+/// the other with the `blocking` feature.
+///
+/// If a request fails, no more requests are sent, and the images fetched
+/// so far are returned along with the error, see [`BulkResponse`].
+///
+/// # Errors
+/// [`NekosBestError::BulkTooLarge`] if `count` is above [`MAX_BULK_COUNT`],
+/// or if no image could be fetched at all, refer to [`NekosBestError`].
+#[cfg_attr(feature = "blocking", blocking)]
+pub async fn get_bulk_with_client(
+    client: &Client,
+    category: impl Into<AnyCategory>,
+    count: usize,
+) -> Result<BulkResponse, NekosBestError> {
+    if count > MAX_BULK_COUNT {
+        return Err(NekosBestError::BulkTooLarge(count));
+    }
+
+    let category = category.into();
+    let mut seen = HashSet::new();
+    let mut results = Vec::new();
+    let mut error = None;
+
+    for _ in 0..=MAX_EXTRA_ROUNDS {
+        let missing = count - results.len();
+        if missing == 0 {
+            break;
+        }
+
+        error = get_pages(client, &category, pages(missing), |resp| {
+            results.extend(
+                resp.0
+                    .into_iter()
+                    .filter(|r: &NekosBestResponseSingle| seen.insert(r.url.clone())),
+            )
+        })
+        .await;
+        results.truncate(count);
+
+        if error.is_some() {
+            break;
+        }
+    }
+
+    match error {
+        Some(e) if results.is_empty() => Err(e),
+        error => Ok(BulkResponse {
+            results,
+            requested: count,
+            error,
+        }),
+    }
+}
+
+/// Gets `count` images, with the default client, see [`get_bulk_with_client`].
+///
+/// # Errors
+/// [`NekosBestError::BulkTooLarge`] if `count` is above [`MAX_BULK_COUNT`],
+/// or if no image could be fetched at all, refer to [`NekosBestError`].
+#[cfg_attr(feature = "blocking", blocking)]
+pub async fn get_bulk(
+    category: impl Into<AnyCategory>,
+    count: usize,
+) -> Result<BulkResponse, NekosBestError> {
+    get_bulk_with_client(default_client(), category, count).await
+}
+
+impl Client {
+    /// Gets `count` images, see [`get_bulk_with_client`].
+    ///
+    /// # Errors
+    /// [`NekosBestError::BulkTooLarge`] if `count` is above [`MAX_BULK_COUNT`],
+    /// or if no image could be fetched at all, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn get_bulk(
+        &self,
+        category: impl Into<AnyCategory>,
+        count: usize,
+    ) -> Result<BulkResponse, NekosBestError> {
+        get_bulk_with_client(self, category, count).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts() {
+        assert_eq!(Amount::new(0), None);
+        assert_eq!(Amount::new(20).map(Amount::get), Some(20));
+        assert_eq!(Amount::try_from(21u8), Err(InvalidAmount(21)));
+        assert_eq!(Amount::try_from(300usize), Err(InvalidAmount(300)));
+
+        assert_eq!(pages(0).next(), None);
+        assert_eq!(pages(20).collect::<Vec<_>>(), [20]);
+        assert_eq!(pages(45).collect::<Vec<_>>(), [20, 20, 5]);
+        assert_eq!(pages(usize::MAX).next(), Some(20));
+    }
+}
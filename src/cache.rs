@@ -0,0 +1,372 @@
+//! An in-memory cache of the responses that don't change often: the
+//! details of an asset, search results and the endpoint listing.
+//!
+//! It is off by default, and enabled per client with
+//! [`ClientBuilder::cache`](crate::client::ClientBuilder::cache) or
+//! [`Client::with_cache`](crate::client::Client::with_cache). Every route has its own time to live, and the
+//! least recently used responses are evicted once the cache is full.
+//!
+//! ```rust ,no_run
+//! # use std::time::Duration;
+//! # use nekosbest::cache::{CacheConfig, CacheRoute};
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), nekosbest::NekosBestError> {
+//! let client = nekosbest::client::Client::builder()
+//!     .cache(CacheConfig {
+//!         search_ttl: Duration::from_secs(60),
+//!         ..CacheConfig::default()
+//!     })
+//!     .build()?;
+//!
+//! let url = "https://nekos.best/api/v2/neko/1efcda2d-d0d3-4e96-9b40-86852374b4bc.png";
+//! client.details(url).await?;
+//! client.details(url).await?; // no request this time
+//!
+//! let stats = client.cache().unwrap().stats(CacheRoute::Details);
+//! assert_eq!((stats.hits, stats.misses), (1, 1));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::details::Details;
+use crate::endpoints::Endpoints;
+use crate::NekosBestResponse;
+
+/// The routes whose responses can be cached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CacheRoute {
+    /// The details of an asset, see [`Client::details`](crate::client::Client::details).
+    Details,
+    /// Search results, see [`Client::search`](crate::client::Client::search).
+    Search,
+    /// The endpoint listing, see [`Client::endpoints`](crate::client::Client::endpoints).
+    Endpoints,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// The maximum number of responses kept, across all the routes.
+    pub capacity: usize,
+    /// How long the details of an asset are kept. They never change,
+    /// so a day by default.
+    pub details_ttl: Duration,
+    /// How long search results are kept, 5 minutes by default.
+    pub search_ttl: Duration,
+    /// How long the endpoint listing is kept, an hour by default.
+    pub endpoints_ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            details_ttl: Duration::from_secs(24 * 60 * 60),
+            search_ttl: Duration::from_secs(5 * 60),
+            endpoints_ttl: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl CacheConfig {
+    /// How long the responses of `route` are kept. A zero duration
+    /// disables the caching of that route.
+    pub fn ttl(&self, route: CacheRoute) -> Duration {
+        match route {
+            CacheRoute::Details => self.details_ttl,
+            CacheRoute::Search => self.search_ttl,
+            CacheRoute::Endpoints => self.endpoints_ttl,
+        }
+    }
+}
+
+/// The hits and misses of a [`ResponseCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl std::ops::Add for CacheStats {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            hits: self.hits + rhs.hits,
+            misses: self.misses + rhs.misses,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum Cached {
+    Details(Details),
+    Search(NekosBestResponse),
+    #[cfg(feature = "strong-types")]
+    SearchJson(serde_json::Value),
+    Endpoints(Endpoints),
+}
+
+/// A response that can be kept in a [`ResponseCache`].
+pub(crate) trait Cacheable: Sized {
+    const ROUTE: CacheRoute;
+
+    fn into_cached(self) -> Cached;
+    fn from_cached(cached: &Cached) -> Option<Self>;
+}
+
+impl Cacheable for Details {
+    const ROUTE: CacheRoute = CacheRoute::Details;
+
+    fn into_cached(self) -> Cached {
+        Cached::Details(self)
+    }
+
+    fn from_cached(cached: &Cached) -> Option<Self> {
+        match cached {
+            Cached::Details(details) => Some(details.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl Cacheable for NekosBestResponse {
+    const ROUTE: CacheRoute = CacheRoute::Search;
+
+    fn into_cached(self) -> Cached {
+        Cached::Search(self)
+    }
+
+    fn from_cached(cached: &Cached) -> Option<Self> {
+        match cached {
+            Cached::Search(resp) => Some(resp.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// The body of a typed search response, kept as JSON so that the
+/// searches of every [`STCategory`](crate::STCategory) share one variant.
+#[cfg(feature = "strong-types")]
+pub(crate) struct SearchJson(pub(crate) serde_json::Value);
+
+#[cfg(feature = "strong-types")]
+impl Cacheable for SearchJson {
+    const ROUTE: CacheRoute = CacheRoute::Search;
+
+    fn into_cached(self) -> Cached {
+        Cached::SearchJson(self.0)
+    }
+
+    fn from_cached(cached: &Cached) -> Option<Self> {
+        match cached {
+            Cached::SearchJson(json) => Some(Self(json.clone())),
+            _ => None,
+        }
+    }
+}
+
+impl Cacheable for Endpoints {
+    const ROUTE: CacheRoute = CacheRoute::Endpoints;
+
+    fn into_cached(self) -> Cached {
+        Cached::Endpoints(self)
+    }
+
+    fn from_cached(cached: &Cached) -> Option<Self> {
+        match cached {
+            Cached::Endpoints(endpoints) => Some(endpoints.clone()),
+            _ => None,
+        }
+    }
+}
+
+type Key = (CacheRoute, String);
+
+struct Entry {
+    value: Cached,
+    expires: Instant,
+    /// When the entry was last used, the key of [`State::lru`].
+    used: u64,
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<Key, Entry>,
+    /// The keys of the entries, least recently used first.
+    lru: BTreeMap<u64, Key>,
+    clock: u64,
+    stats: HashMap<CacheRoute, CacheStats>,
+}
+
+impl State {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn remove(&mut self, key: &Key) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.lru.remove(&entry.used);
+        }
+    }
+}
+
+/// The cache of a [`Client`](crate::client::Client), shared by its clones.
+pub struct ResponseCache {
+    config: CacheConfig,
+    state: Mutex<State>,
+}
+
+impl ResponseCache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The hits and misses of `route`.
+    pub fn stats(&self, route: CacheRoute) -> CacheStats {
+        self.state().stats.get(&route).copied().unwrap_or_default()
+    }
+
+    /// The hits and misses of all the routes.
+    pub fn total_stats(&self) -> CacheStats {
+        self.state().stats.values().fold(CacheStats::default(), |a, b| a + *b)
+    }
+
+    /// The number of responses kept, including the expired ones
+    /// not evicted yet.
+    pub fn len(&self) -> usize {
+        self.state().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all the responses, keeping the stats.
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.entries.clear();
+        state.lru.clear();
+    }
+
+    pub(crate) fn get<T: Cacheable>(&self, key: &str) -> Option<T> {
+        if self.config.ttl(T::ROUTE).is_zero() {
+            return None;
+        }
+
+        let key = (T::ROUTE, key.to_owned());
+        let mut state = self.state();
+        let now = Instant::now();
+        let tick = state.tick();
+
+        let value = match state.entries.get_mut(&key) {
+            Some(entry) if entry.expires > now => T::from_cached(&entry.value).map(|value| {
+                let used = std::mem::replace(&mut entry.used, tick);
+                (value, used)
+            }),
+            Some(_) => {
+                state.remove(&key);
+                None
+            }
+            None => None,
+        };
+
+        let stats = state.stats.entry(T::ROUTE).or_default();
+        match value {
+            Some((value, used)) => {
+                stats.hits += 1;
+                state.lru.remove(&used);
+                state.lru.insert(tick, key);
+                Some(value)
+            }
+            None => {
+                stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub(crate) fn insert<T: Cacheable>(&self, key: &str, value: T) {
+        let ttl = self.config.ttl(T::ROUTE);
+        if ttl.is_zero() || self.config.capacity == 0 {
+            return;
+        }
+
+        let key = (T::ROUTE, key.to_owned());
+        let mut state = self.state();
+        state.remove(&key);
+        while state.entries.len() >= self.config.capacity {
+            let Some((_, oldest)) = state.lru.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+
+        let used = state.tick();
+        state.lru.insert(used, key.clone());
+        state.entries.insert(
+            key,
+            Entry {
+                value: value.into_cached(),
+                expires: Instant::now() + ttl,
+                used,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(n: usize) -> NekosBestResponse {
+        serde_json::from_str(&format!(r#"{{"results": [{{"url": "{n}", "anime_name": ""}}]}}"#))
+            .unwrap()
+    }
+
+    #[test]
+    fn lru_and_ttl() {
+        let cache = ResponseCache::new(CacheConfig {
+            capacity: 2,
+            endpoints_ttl: Duration::ZERO,
+            ..CacheConfig::default()
+        });
+
+        cache.insert("a", search(1));
+        cache.insert("b", search(2));
+        // "a" is now more recently used than "b"
+        assert!(cache.get::<NekosBestResponse>("a").is_some());
+        cache.insert("c", search(3));
+        assert!(cache.get::<NekosBestResponse>("b").is_none());
+        assert!(cache.get::<NekosBestResponse>("a").is_some());
+        assert!(cache.get::<NekosBestResponse>("c").is_some());
+        assert_eq!(cache.len(), 2);
+
+        // same key, other route
+        assert!(cache.get::<Details>("a").is_none());
+
+        // a zero ttl disables the route
+        cache.insert("e", Endpoints(Default::default()));
+        assert!(cache.get::<Endpoints>("e").is_none());
+        assert_eq!(cache.stats(CacheRoute::Endpoints), CacheStats::default());
+
+        assert_eq!(cache.stats(CacheRoute::Search), CacheStats { hits: 3, misses: 1 });
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -1,9 +1,11 @@
 use std::{convert::TryFrom, str::FromStr};
 
+use crate::details::DetailsKind;
+
 macro_rules! categories {
     ($(
         $(#[$at:meta])* $(ref $(#[$ref_at:meta])*)?
-        $cat_name:ident => $url_name:literal,
+        $cat_name:ident => $url_name:literal: $format:ident,
     )*) => {
         #[derive(
             Debug,
@@ -53,6 +55,16 @@ macro_rules! categories {
                 }
             }
 
+            /// The format of the media served by this category.
+            pub const fn format(self) -> Format {
+                match self {
+                    $(
+                        $(#[$ref_at])*
+                        Category::$cat_name => Format::$format,
+                    )*
+                }
+            }
+
             pub const ALL_VARIANTS: &[Category] = &[
                 $(
                     $(#[$ref_at])*
@@ -63,53 +75,125 @@ macro_rules! categories {
     };
 }
 
-categories! {
-    Angry => "angry",
-    Baka => "baka",
-    Bite => "bite",
-    Blush => "blush",
-    Bored => "bored",
-    Cry => "cry",
-    Cuddle => "cuddle",
-    Dance => "dance",
-    Facepalm => "facepalm",
-    Feed => "feed",
-    Handhold => "handhold",
-    Handshake => "handshake",
-    Happy => "happy",
-    Highfive => "highfive",
-    Hug => "hug",
-    Husbando => "husbando",
-    Kick => "kick",
-    Kiss => "kiss",
-    Kitsune => "kitsune",
-    Laugh => "laugh",
-    Lurk => "lurk",
-    Neko => "neko",
-    Nod => "nod",
-    Nom => "nom",
-    Nope => "nope",
-    Pat => "pat",
-    Peck => "peck",
-    Poke => "poke",
-    Pout => "pout",
-    Punch => "punch",
-    Run => "run",
-    Shoot => "shoot",
-    Shrug => "shrug",
-    Slap => "slap",
-    Sleep => "sleep",
-    Smile => "smile",
-    Smug => "smug",
-    Stare => "stare",
-    Think => "think",
-    ThumbsUp => "thumbsup",
-    Tickle => "tickle",
-    Waifu => "waifu",
-    Wave => "wave",
-    Wink => "wink",
-    Yawn => "yawn",
-    Yeet => "yeet",
+/// The single declaration of every category, with its url name and format.
+///
+/// Invokes `$callback!` with the whole list, so everything generated from it
+/// ([`Category`], and the strong types with the `strong-types` feature)
+/// stays in sync. To add a category, add it here, then to the tables of
+/// `names.rs` and `interaction.rs`, whose exhaustive matches fail to compile
+/// until the new category is in them.
+macro_rules! for_each_category {
+    ($callback:ident) => {
+        $callback! {
+            Angry => "angry": Gif,
+            Baka => "baka": Gif,
+            Bite => "bite": Gif,
+            Blush => "blush": Gif,
+            Bored => "bored": Gif,
+            Cry => "cry": Gif,
+            Cuddle => "cuddle": Gif,
+            Dance => "dance": Gif,
+            Facepalm => "facepalm": Gif,
+            Feed => "feed": Gif,
+            Handhold => "handhold": Gif,
+            Handshake => "handshake": Gif,
+            Happy => "happy": Gif,
+            Highfive => "highfive": Gif,
+            Hug => "hug": Gif,
+            Husbando => "husbando": Png,
+            Kick => "kick": Gif,
+            Kiss => "kiss": Gif,
+            Kitsune => "kitsune": Png,
+            Laugh => "laugh": Gif,
+            Lurk => "lurk": Gif,
+            Neko => "neko": Png,
+            Nod => "nod": Gif,
+            Nom => "nom": Gif,
+            Nope => "nope": Gif,
+            Pat => "pat": Gif,
+            Peck => "peck": Gif,
+            Poke => "poke": Gif,
+            Pout => "pout": Gif,
+            Punch => "punch": Gif,
+            Run => "run": Gif,
+            Shoot => "shoot": Gif,
+            Shrug => "shrug": Gif,
+            Slap => "slap": Gif,
+            Sleep => "sleep": Gif,
+            Smile => "smile": Gif,
+            Smug => "smug": Gif,
+            Stare => "stare": Gif,
+            Think => "think": Gif,
+            ThumbsUp => "thumbsup": Gif,
+            Tickle => "tickle": Gif,
+            Waifu => "waifu": Png,
+            Wave => "wave": Gif,
+            Wink => "wink": Gif,
+            Yawn => "yawn": Gif,
+            Yeet => "yeet": Gif,
+        }
+    };
+}
+
+#[cfg(feature = "strong-types")]
+pub(crate) use for_each_category;
+
+for_each_category!(categories);
+
+/// The format of the media served by a category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Format {
+    Png,
+    Gif,
+    /// A format this version of the crate doesn't know about.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Category {
+    /// The kind of [`Details`](crate::details::Details) the API returns for this category.
+    pub const fn details_kind(self) -> DetailsKind {
+        match self.format() {
+            Format::Png => DetailsKind::Image,
+            Format::Gif | Format::Unknown => DetailsKind::Gif,
+        }
+    }
+
+    /// The categories serving images, with [`ImageDetails`](crate::details::ImageDetails).
+    pub const IMAGE_VARIANTS: &'static [Category] =
+        &Self::variants_with_format::<{ Self::count_with_format(Format::Png) }>(Format::Png);
+
+    /// The categories serving gifs, with [`GifDetails`](crate::details::GifDetails).
+    pub const GIF_VARIANTS: &'static [Category] =
+        &Self::variants_with_format::<{ Self::count_with_format(Format::Gif) }>(Format::Gif);
+
+    const fn count_with_format(format: Format) -> usize {
+        let mut count = 0;
+        let mut i = 0;
+        while i < Self::ALL_VARIANTS.len() {
+            if Self::ALL_VARIANTS[i].format() as u8 == format as u8 {
+                count += 1;
+            }
+            i += 1;
+        }
+        count
+    }
+
+    const fn variants_with_format<const N: usize>(format: Format) -> [Category; N] {
+        let mut variants = [Self::ALL_VARIANTS[0]; N];
+        let mut n = 0;
+        let mut i = 0;
+        while i < Self::ALL_VARIANTS.len() {
+            if Self::ALL_VARIANTS[i].format() as u8 == format as u8 {
+                variants[n] = Self::ALL_VARIANTS[i];
+                n += 1;
+            }
+            i += 1;
+        }
+        variants
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -124,9 +208,9 @@ impl TryFrom<String> for Category {
     }
 }
 
-impl Into<String> for Category {
-    fn into(self) -> String {
-        self.to_url_name().to_owned()
+impl From<Category> for String {
+    fn from(category: Category) -> Self {
+        category.to_url_name().to_owned()
     }
 }
 
@@ -135,3 +219,148 @@ impl std::fmt::Display for Category {
         self.to_url_name().fmt(f)
     }
 }
+
+/// A category, which may not be known to this version of the crate.
+///
+/// Lets you request categories added to the API after this crate was
+/// released, and keeps parsing working against a newer server.
+///
+/// ```rust
+/// # use nekosbest::{AnyCategory, Category};
+/// assert_eq!("neko".parse::<AnyCategory>(), Ok(AnyCategory::Known(Category::Neko)));
+/// assert_eq!(
+///     "brand-new".parse::<AnyCategory>(),
+///     Ok(AnyCategory::Other("brand-new".to_owned())),
+/// );
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(into = "String", from = "String")]
+pub enum AnyCategory {
+    Known(Category),
+    /// The url name of a category this crate doesn't know about.
+    ///
+    /// Must not be the name of a [`Category`]: `Other("neko".to_owned())`
+    /// is not equal to `Known(Category::Neko)`, though they display and
+    /// serialize the same. Build it with `From` or [`FromStr`], which pick
+    /// [`Known`](Self::Known) for the known names.
+    Other(String),
+}
+
+impl AnyCategory {
+    /// The name of the category, as it appears in urls.
+    pub fn url_name(&self) -> &str {
+        match self {
+            AnyCategory::Known(category) => category.to_url_name(),
+            AnyCategory::Other(name) => name,
+        }
+    }
+
+    /// The [`Category`], if it is known to this version of the crate.
+    pub fn known(&self) -> Option<Category> {
+        match self {
+            AnyCategory::Known(category) => Some(*category),
+            AnyCategory::Other(_) => None,
+        }
+    }
+}
+
+impl From<Category> for AnyCategory {
+    fn from(category: Category) -> Self {
+        AnyCategory::Known(category)
+    }
+}
+
+impl From<String> for AnyCategory {
+    fn from(name: String) -> Self {
+        match Category::from_url_name(&name) {
+            Some(category) => AnyCategory::Known(category),
+            None => AnyCategory::Other(name),
+        }
+    }
+}
+
+impl From<&str> for AnyCategory {
+    fn from(name: &str) -> Self {
+        match Category::from_url_name(name) {
+            Some(category) => AnyCategory::Known(category),
+            None => AnyCategory::Other(name.to_owned()),
+        }
+    }
+}
+
+impl FromStr for AnyCategory {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.into())
+    }
+}
+
+impl From<AnyCategory> for String {
+    fn from(category: AnyCategory) -> Self {
+        match category {
+            AnyCategory::Known(category) => category.into(),
+            AnyCategory::Other(name) => name,
+        }
+    }
+}
+
+impl std::fmt::Display for AnyCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.url_name().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_categories() {
+        let other = AnyCategory::from("brand-new");
+        assert_eq!(other, AnyCategory::Other("brand-new".to_owned()));
+        assert_eq!(other.to_string(), "brand-new");
+        assert_eq!(other.known(), None);
+        assert_eq!(AnyCategory::from("neko").known(), Some(Category::Neko));
+
+        let json = serde_json::to_string(&other).unwrap();
+        assert_eq!(json, r#""brand-new""#);
+        assert_eq!(serde_json::from_str::<AnyCategory>(&json).unwrap(), other);
+        assert_eq!(
+            serde_json::from_str::<AnyCategory>(r#""hug""#).unwrap(),
+            AnyCategory::Known(Category::Hug)
+        );
+    }
+
+    #[test]
+    fn category_formats() {
+        assert_eq!(
+            Category::IMAGE_VARIANTS,
+            [
+                Category::Husbando,
+                Category::Kitsune,
+                Category::Neko,
+                Category::Waifu
+            ]
+        );
+        assert_eq!(
+            Category::IMAGE_VARIANTS.len() + Category::GIF_VARIANTS.len(),
+            Category::ALL_VARIANTS.len()
+        );
+        assert!(Category::GIF_VARIANTS
+            .iter()
+            .all(|category| category.format() == Format::Gif));
+        assert_eq!(Category::Neko.details_kind(), DetailsKind::Image);
+        assert_eq!(Category::Hug.details_kind(), DetailsKind::Gif);
+    }
+}
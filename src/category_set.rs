@@ -0,0 +1,361 @@
+//! Sets of categories, and getting an image from a random category
+//! of a set, like "any of hug, cuddle or pat".
+//!
+//! ```rust
+//! # use nekosbest::{Category, CategorySet};
+//! let affection = CategorySet::from_slice(&[Category::Hug, Category::Cuddle, Category::Pat]);
+//! assert!(affection.contains(Category::Pat));
+//!
+//! let violent = CategorySet::from_slice(&[Category::Slap, Category::Punch, Category::Kick]);
+//! let peaceful_gifs = CategorySet::GIFS - violent;
+//! assert!(!peaceful_gifs.contains(Category::Slap));
+//! assert!(peaceful_gifs.is_superset(affection));
+//! ```
+
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub, SubAssign};
+
+#[cfg(feature = "blocking")]
+use nb_blocking_util::blocking;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::IteratorRandom;
+
+use crate::category::Format;
+use crate::client::{default_client, Client};
+use crate::{Category, NekosBestError, NekosBestResponseSingle};
+
+// every category must fit in the bitset
+const _: () = assert!(Category::ALL_VARIANTS.len() <= u64::BITS as usize);
+
+/// A set of [`Category`], stored as a bitset.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CategorySet(u64);
+
+impl CategorySet {
+    pub const EMPTY: Self = Self(0);
+    pub const ALL: Self = Self::from_slice(Category::ALL_VARIANTS);
+    /// The categories serving gifs, see [`Category::GIF_VARIANTS`].
+    pub const GIFS: Self = Self::from_slice(Category::GIF_VARIANTS);
+    /// The categories serving images, see [`Category::IMAGE_VARIANTS`].
+    pub const IMAGES: Self = Self::from_slice(Category::IMAGE_VARIANTS);
+
+    pub const fn new() -> Self {
+        Self::EMPTY
+    }
+
+    pub const fn from_slice(categories: &[Category]) -> Self {
+        let mut set = Self::EMPTY;
+        let mut i = 0;
+        while i < categories.len() {
+            set = set.with(categories[i]);
+            i += 1;
+        }
+        set
+    }
+
+    /// The categories with the given format.
+    pub const fn with_format(format: Format) -> Self {
+        match format {
+            Format::Png => Self::IMAGES,
+            Format::Gif => Self::GIFS,
+            Format::Unknown => Self::EMPTY,
+        }
+    }
+
+    const fn bit(category: Category) -> u64 {
+        1 << category as u32
+    }
+
+    /// This set, with `category` added.
+    pub const fn with(self, category: Category) -> Self {
+        Self(self.0 | Self::bit(category))
+    }
+
+    /// This set, with `category` removed.
+    pub const fn without(self, category: Category) -> Self {
+        Self(self.0 & !Self::bit(category))
+    }
+
+    /// Adds `category`, returning whether it was not in the set yet.
+    pub fn insert(&mut self, category: Category) -> bool {
+        let added = !self.contains(category);
+        *self = self.with(category);
+        added
+    }
+
+    /// Removes `category`, returning whether it was in the set.
+    pub fn remove(&mut self, category: Category) -> bool {
+        let removed = self.contains(category);
+        *self = self.without(category);
+        removed
+    }
+
+    pub const fn contains(self, category: Category) -> bool {
+        self.0 & Self::bit(category) != 0
+    }
+
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// The categories not in this set.
+    pub const fn complement(self) -> Self {
+        Self::ALL.difference(self)
+    }
+
+    pub const fn is_subset(self, other: Self) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    pub const fn is_superset(self, other: Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// The categories in this set, in the order of [`Category::ALL_VARIANTS`].
+    pub fn iter(self) -> impl Iterator<Item = Category> {
+        Category::ALL_VARIANTS
+            .iter()
+            .copied()
+            .filter(move |category| self.contains(*category))
+    }
+
+    /// A random category of this set, if it is not empty.
+    pub fn choose(self) -> Option<Category> {
+        self.iter().choose(&mut rand::thread_rng())
+    }
+}
+
+impl std::fmt::Debug for CategorySet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl From<Category> for CategorySet {
+    fn from(category: Category) -> Self {
+        Self::EMPTY.with(category)
+    }
+}
+
+impl FromIterator<Category> for CategorySet {
+    fn from_iter<I: IntoIterator<Item = Category>>(iter: I) -> Self {
+        let mut set = Self::EMPTY;
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Category> for CategorySet {
+    fn extend<I: IntoIterator<Item = Category>>(&mut self, iter: I) {
+        for category in iter {
+            self.insert(category);
+        }
+    }
+}
+
+impl IntoIterator for CategorySet {
+    type Item = Category;
+    type IntoIter = Box<dyn Iterator<Item = Category>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+impl BitOr for CategorySet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+impl BitOrAssign for CategorySet {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = self.union(rhs);
+    }
+}
+
+impl BitAnd for CategorySet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        self.intersection(rhs)
+    }
+}
+
+impl BitAndAssign for CategorySet {
+    fn bitand_assign(&mut self, rhs: Self) {
+        *self = self.intersection(rhs);
+    }
+}
+
+impl Sub for CategorySet {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.difference(rhs)
+    }
+}
+
+impl SubAssign for CategorySet {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = self.difference(rhs);
+    }
+}
+
+impl Not for CategorySet {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        self.complement()
+    }
+}
+
+/// Chooses a category, with a probability proportional to its weight.
+/// Returns `None` if all the weights are 0.
+pub fn choose_weighted(weights: &[(Category, u32)]) -> Option<Category> {
+    // summed as u64, so that large u32 weights can't overflow
+    let index = WeightedIndex::new(weights.iter().map(|(_, weight)| u64::from(*weight))).ok()?;
+
+    Some(weights[index.sample(&mut rand::thread_rng())].0)
+}
+
+/// A response from a randomly chosen category.
+#[derive(Debug, Clone)]
+pub struct ChosenResponse {
+    /// The category that was chosen.
+    pub category: Category,
+    pub response: NekosBestResponseSingle,
+}
+
+/// Gets a single image from a random category of `set`, with a supplied client.
+///
+/// # Errors
+/// [`NekosBestError::EmptyChoice`] if `set` is empty, or any errors
+/// that can happen, refer to [`NekosBestError`].
+#[cfg_attr(feature = "blocking", blocking)]
+pub async fn get_any_with_client(
+    client: &Client,
+    set: CategorySet,
+) -> Result<ChosenResponse, NekosBestError> {
+    let category = set.choose().ok_or(NekosBestError::EmptyChoice)?;
+    let response = crate::get_with_client(client, category).await?;
+
+    Ok(ChosenResponse { category, response })
+}
+
+/// Gets a single image from a random category of `set`, with the default client.
+///
+/// # Errors
+/// [`NekosBestError::EmptyChoice`] if `set` is empty, or any errors
+/// that can happen, refer to [`NekosBestError`].
+#[cfg_attr(feature = "blocking", blocking)]
+pub async fn get_any(set: CategorySet) -> Result<ChosenResponse, NekosBestError> {
+    get_any_with_client(default_client(), set).await
+}
+
+/// Gets a single image from a category chosen with [`choose_weighted`],
+/// with a supplied client.
+///
+/// # Errors
+/// [`NekosBestError::EmptyChoice`] if all the weights are 0, or any errors
+/// that can happen, refer to [`NekosBestError`].
+#[cfg_attr(feature = "blocking", blocking)]
+pub async fn get_weighted_with_client(
+    client: &Client,
+    weights: &[(Category, u32)],
+) -> Result<ChosenResponse, NekosBestError> {
+    let category = choose_weighted(weights).ok_or(NekosBestError::EmptyChoice)?;
+    let response = crate::get_with_client(client, category).await?;
+
+    Ok(ChosenResponse { category, response })
+}
+
+/// Gets a single image from a category chosen with [`choose_weighted`],
+/// with the default client.
+///
+/// # Errors
+/// [`NekosBestError::EmptyChoice`] if all the weights are 0, or any errors
+/// that can happen, refer to [`NekosBestError`].
+#[cfg_attr(feature = "blocking", blocking)]
+pub async fn get_weighted(weights: &[(Category, u32)]) -> Result<ChosenResponse, NekosBestError> {
+    get_weighted_with_client(default_client(), weights).await
+}
+
+impl Client {
+    /// Gets a single image from a random category of `set`, see [`get_any_with_client`].
+    ///
+    /// # Errors
+    /// Any errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn get_any(&self, set: CategorySet) -> Result<ChosenResponse, NekosBestError> {
+        get_any_with_client(self, set).await
+    }
+
+    /// Gets a single image from a category chosen by weight,
+    /// see [`get_weighted_with_client`].
+    ///
+    /// # Errors
+    /// Any errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn get_weighted(
+        &self,
+        weights: &[(Category, u32)],
+    ) -> Result<ChosenResponse, NekosBestError> {
+        get_weighted_with_client(self, weights).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn category_sets() {
+        assert_eq!(CategorySet::ALL.len(), Category::ALL_VARIANTS.len());
+        assert_eq!(CategorySet::GIFS | CategorySet::IMAGES, CategorySet::ALL);
+        assert_eq!(!CategorySet::GIFS, CategorySet::IMAGES);
+        assert!((CategorySet::GIFS & CategorySet::IMAGES).is_empty());
+
+        let mut set = CategorySet::from_slice(&[Category::Pat, Category::Hug]);
+        assert!(set.insert(Category::Cuddle));
+        assert!(!set.insert(Category::Cuddle));
+        assert!(set.remove(Category::Pat));
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            [Category::Cuddle, Category::Hug]
+        );
+        assert_eq!(format!("{set:?}"), "{Cuddle, Hug}");
+        assert!(set.is_subset(CategorySet::GIFS));
+        assert!(set.contains(set.choose().unwrap()));
+        assert_eq!(CategorySet::EMPTY.choose(), None);
+
+        assert_eq!(
+            choose_weighted(&[(Category::Hug, 0), (Category::Pat, 3)]),
+            Some(Category::Pat)
+        );
+        assert_eq!(choose_weighted(&[(Category::Hug, 0)]), None);
+        assert_eq!(
+            choose_weighted(&[(Category::Hug, u32::MAX), (Category::Pat, 0)]),
+            Some(Category::Hug)
+        );
+        let weights = [(Category::Hug, u32::MAX), (Category::Pat, u32::MAX), (Category::Wave, 1)];
+        assert!(choose_weighted(&weights).is_some());
+    }
+}
@@ -1,13 +1,24 @@
-use std::ops::Add;
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use tokio::time::Instant;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+#[cfg(feature = "blocking")]
+use nb_blocking_util::blocking;
+use rand::Rng;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{IntoUrl, StatusCode};
+
+use crate::ratelimit::Ratelimits;
 
 #[cfg(not(feature = "blocking"))]
 pub(crate) type ReqwestClient = reqwest::Client;
 #[cfg(feature = "blocking")]
 pub(crate) type ReqwestClient = reqwest::blocking::Client;
 
+#[cfg(not(feature = "blocking"))]
+type ReqwestClientBuilder = reqwest::ClientBuilder;
+#[cfg(feature = "blocking")]
+type ReqwestClientBuilder = reqwest::blocking::ClientBuilder;
+
 #[cfg(not(feature = "blocking"))]
 pub(crate) type ReqBuilder = reqwest::RequestBuilder;
 #[cfg(feature = "blocking")]
@@ -18,27 +29,371 @@ pub(crate) type ReqwestResponse = reqwest::Response;
 #[cfg(feature = "blocking")]
 pub(crate) type ReqwestResponse = reqwest::blocking::Response;
 
-struct SearchRatelimitData {
-    remaining: u32,
-    resets_at: Instant,
-}
-
+/// What to do when a request would exceed the rate limit reported by the server.
 #[derive(Copy, Clone, Default)]
-pub enum SearchRatelimitBehavior {
+pub enum RatelimitBehavior {
+    /// Wait until the rate limit resets, then send the request.
     #[default]
     Sleep,
+    /// Fail with [`NekosBestError::RateLimited`](crate::NekosBestError::RateLimited).
     Error,
 }
 
-#[derive(Default, Copy, Clone)]
+pub type SearchRatelimitBehavior = RatelimitBehavior;
+
+/// A group of routes that share a rate limit.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RatelimitBucket {
+    /// The random image / gif endpoints, e.g. `/neko`.
+    Category,
+    /// `/search`.
+    Search,
+    /// `/metrics`.
+    Metrics,
+    /// The images and gifs themselves, when getting their details or downloading them.
+    Assets,
+}
+
+/// How requests that failed with a transient error are retried.
+///
+/// Connect errors, timeouts, `5xx` responses and `429 Too Many Requests`
+/// are retried, with an exponential backoff between the attempts. A
+/// `Retry-After` header sent along with the response takes precedence over
+/// the computed backoff, but is capped by `max_backoff` too.
+///
+/// Only the sending of the request is retried; once the body of a response
+/// started being consumed (e.g. while streaming a download to a file), errors
+/// are returned as-is, since the partial result cannot be safely replayed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    /// `1` disables retrying altogether.
+    pub max_attempts: u32,
+    /// The backoff after the first failed attempt, doubled on every
+    /// subsequent failure.
+    pub initial_backoff: Duration,
+    /// The upper bound of the backoff, also applied to `Retry-After`.
+    pub max_backoff: Duration,
+    /// Whether to randomize the backoff, to avoid many clients
+    /// retrying in lockstep.
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// The time to wait after the `attempt`-th failed attempt (starting from 1).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(31);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << exp)
+            .min(self.max_backoff);
+
+        if self.jitter {
+            // "equal jitter": keep half of the backoff, randomize the other half.
+            let half = backoff / 2;
+            half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+        } else {
+            backoff
+        }
+    }
+
+    /// The time to wait after the `attempt`-th failed attempt, honouring
+    /// the `Retry-After` header of the response, up to `max_backoff`.
+    pub(crate) fn delay(&self, attempt: u32, headers: &reqwest::header::HeaderMap) -> Duration {
+        retry_after(headers).map_or_else(|| self.backoff(attempt), |d| d.min(self.max_backoff))
+    }
+
+    pub(crate) fn should_retry_status(status: StatusCode) -> bool {
+        status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+    }
+
+    pub(crate) fn should_retry_error(e: &reqwest::Error) -> bool {
+        e.is_connect() || e.is_timeout()
+    }
+}
+
+/// Parses the `Retry-After` header, in its delay-seconds form.
+pub(crate) fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let secs = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse::<u64>()
+        .ok()?;
+
+    Some(Duration::from_secs(secs))
+}
+
+#[cfg(not(feature = "blocking"))]
+async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+#[cfg(feature = "blocking")]
+fn sleep(duration: Duration) {
+    std::thread::sleep(duration);
+}
+
+#[derive(Clone)]
 pub struct ClientConfig {
-    pub search_ratelimit_behavior: SearchRatelimitBehavior,
+    pub search_ratelimit_behavior: RatelimitBehavior,
+    pub category_ratelimit_behavior: RatelimitBehavior,
+    pub metrics_ratelimit_behavior: RatelimitBehavior,
+    pub assets_ratelimit_behavior: RatelimitBehavior,
+    /// How to retry requests that failed with a transient error.
+    ///
+    /// Can be overridden for a single call with [`Client::with_retry_policy`].
+    pub retry_policy: RetryPolicy,
+    /// The root of the API, without the version, e.g. `https://nekos.best/api`.
+    ///
+    /// Can be pointed at a mock server, a caching proxy or a mirror.
+    pub api_root: String,
+    /// The version of the API to use, appended to [`api_root`](Self::api_root)
+    /// as `/v{api_version}`.
+    pub api_version: usize,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            search_ratelimit_behavior: RatelimitBehavior::default(),
+            category_ratelimit_behavior: RatelimitBehavior::default(),
+            metrics_ratelimit_behavior: RatelimitBehavior::default(),
+            assets_ratelimit_behavior: RatelimitBehavior::default(),
+            retry_policy: RetryPolicy::default(),
+            api_root: crate::API_ROOT.to_owned(),
+            api_version: crate::API_VERSION,
+        }
+    }
 }
 
+impl ClientConfig {
+    /// The url all the endpoints are relative to, e.g. `https://nekos.best/api/v2`.
+    pub fn base_url(&self) -> String {
+        format!(
+            "{}/v{}",
+            self.api_root.trim_end_matches('/'),
+            self.api_version
+        )
+    }
+
+    /// What to do when a request to the given bucket would exceed its rate limit.
+    pub fn ratelimit_behavior(&self, bucket: RatelimitBucket) -> RatelimitBehavior {
+        match bucket {
+            RatelimitBucket::Category => self.category_ratelimit_behavior,
+            RatelimitBucket::Search => self.search_ratelimit_behavior,
+            RatelimitBucket::Metrics => self.metrics_ratelimit_behavior,
+            RatelimitBucket::Assets => self.assets_ratelimit_behavior,
+        }
+    }
+}
+
+/// A builder for a [`Client`], to configure the underlying HTTP client
+/// without depending on `reqwest` directly.
+///
+/// All the settings default to the ones used by [`Client::new`].
+///
+/// ```rust ,no_run
+/// # use std::time::Duration;
+/// # fn main() -> Result<(), nekosbest::NekosBestError> {
+/// let client = nekosbest::client::Client::builder()
+///     .timeout(Duration::from_secs(10))
+///     .connect_timeout(Duration::from_secs(3))
+///     .contact("https://example.com/my-bot")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+#[must_use]
+pub struct ClientBuilder {
+    config: ClientConfig,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxy: Option<String>,
+    user_agent: Option<String>,
+    contact: Option<String>,
+    default_headers: Vec<(String, String)>,
+    pool_idle_timeout: Option<Option<Duration>>,
+    pool_max_idle_per_host: Option<usize>,
+    cache: Option<crate::cache::CacheConfig>,
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The [`ClientConfig`] of the client.
+    pub fn config(mut self, config: ClientConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// The timeout of every request, from connecting until the
+    /// response body has been read. No timeout by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The timeout for connecting to the server. No timeout by default.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Proxy all the requests through the given proxy url,
+    /// e.g. `http://localhost:3128` or `socks5://localhost:1080`.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Replaces the default user agent.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Contact information (e.g. an url or an email) for the operators of
+    /// the API, appended to the user agent.
+    pub fn contact(mut self, contact: impl Into<String>) -> Self {
+        self.contact = Some(contact.into());
+        self
+    }
+
+    /// A header sent with every request.
+    pub fn default_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.default_headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// How long idle connections are kept in the pool,
+    /// `None` to keep them forever. 90 seconds by default.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// The maximum number of idle connections kept per host.
+    /// Unlimited by default.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Caches the responses that don't change often, see [`cache`](crate::cache).
+    /// No cache by default.
+    pub fn cache(mut self, config: crate::cache::CacheConfig) -> Self {
+        self.cache = Some(config);
+        self
+    }
+
+    /// Builds the client.
+    ///
+    /// # Errors
+    /// If the proxy url or one of the headers is invalid, or
+    /// the underlying HTTP client could not be created.
+    pub fn build(self) -> Result<Client, crate::NekosBestError> {
+        let user_agent = self
+            .user_agent
+            .as_deref()
+            .unwrap_or(crate::API_CLIENT_AGENT);
+        let user_agent = match &self.contact {
+            Some(contact) => format!("{user_agent} (+{contact})"),
+            None => user_agent.to_owned(),
+        };
+        let user_agent = HeaderValue::from_str(&user_agent)
+            .map_err(|_| crate::NekosBestError::InvalidHeader("user-agent".to_owned()))?;
+
+        let mut default_headers = HeaderMap::new();
+        for (name, value) in &self.default_headers {
+            let invalid = || crate::NekosBestError::InvalidHeader(name.clone());
+            default_headers.append(
+                HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?,
+                HeaderValue::from_str(value).map_err(|_| invalid())?,
+            );
+        }
+
+        let mut builder = ReqwestClientBuilder::new().default_headers(default_headers);
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+
+        let mut client = Client::new_with_reqwest_client(builder.build()?, self.config);
+        client.user_agent = user_agent;
+        client.cache = self
+            .cache
+            .map(|config| Arc::new(crate::cache::ResponseCache::new(config)));
+        Ok(client)
+    }
+}
+
+/// A client for the API.
+///
+/// Cloning a client is cheap, and the clones share the same
+/// connection pool and rate limit state.
+#[derive(Clone)]
 pub struct Client {
     pub(crate) client: ReqwestClient,
-    client_config: ClientConfig,
-    search_ratelimit_data: Arc<Mutex<Option<SearchRatelimitData>>>,
+    client_config: Arc<ClientConfig>,
+    base_url: Arc<str>,
+    user_agent: HeaderValue,
+    ratelimits: Arc<Ratelimits>,
+    cache: Option<Arc<crate::cache::ResponseCache>>,
+    #[cfg(feature = "vcr")]
+    cassette: Option<crate::vcr::Cassette>,
+}
+
+static DEFAULT_CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// The process-wide client used by the functions that don't take
+/// one, like [`get`](crate::get) or [`search`](crate::search).
+///
+/// It is created with [`ClientConfig::default`] when first used,
+/// unless replaced before that with [`set_default_client`].
+pub fn default_client() -> &'static Client {
+    DEFAULT_CLIENT.get_or_init(|| Client::new(ClientConfig::default()))
+}
+
+/// Replaces the process-wide default client, see [`default_client`].
+///
+/// Meant to be called once at startup: if the default client was
+/// already set or used, `client` is given back as an error.
+pub fn set_default_client(client: Client) -> Result<(), Client> {
+    DEFAULT_CLIENT.set(client)
 }
 
 impl Client {
@@ -49,51 +404,278 @@ impl Client {
     pub fn new_with_reqwest_client(client: ReqwestClient, client_config: ClientConfig) -> Self {
         Self {
             client,
-            client_config,
-            search_ratelimit_data: Arc::new(Mutex::new(None)),
-        }
-    }
-
-    pub(crate) async fn handle_search_ratelimit(&self) -> Result<(), crate::NekosBestError> {
-        let lock = self.search_ratelimit_data.lock().await;
-        if let Some(search_ratelimit_data) = &*lock {
-            if search_ratelimit_data.remaining == 0 {
-                match self.client_config.search_ratelimit_behavior {
-                    SearchRatelimitBehavior::Sleep => {
-                        tokio::time::sleep_until(search_ratelimit_data.resets_at).await;
-                    }
-                    SearchRatelimitBehavior::Error => {
-                        return Err(crate::NekosBestError::RateLimited);
-                    }
+            base_url: client_config.base_url().into(),
+            client_config: Arc::new(client_config),
+            user_agent: HeaderValue::from_static(crate::API_CLIENT_AGENT),
+            ratelimits: Arc::new(Ratelimits::default()),
+            cache: None,
+            #[cfg(feature = "vcr")]
+            cassette: None,
+        }
+    }
+
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// The url all the endpoints are relative to, see [`ClientConfig::base_url`].
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// The url of the endpoint `segment`, which is percent-encoded so that
+    /// category names like `../search` or `a?b` can't leave their segment.
+    pub(crate) fn endpoint_url(&self, segment: impl std::fmt::Display) -> String {
+        let segment = segment.to_string();
+        let mut url = match url::Url::parse(&self.base_url) {
+            Ok(url) if !url.cannot_be_a_base() => url,
+            // an invalid base url is reported when sending the request
+            _ => return format!("{}/{segment}", self.base_url),
+        };
+        url.path_segments_mut()
+            .expect("the base url can be a base")
+            .pop_if_empty()
+            .push(&segment);
+
+        url.into()
+    }
+
+    #[cfg(not(feature = "blocking"))]
+    pub(crate) fn retry_policy(&self) -> RetryPolicy {
+        self.client_config.retry_policy
+    }
+
+    pub(crate) fn get_request(&self, url: impl IntoUrl) -> ReqBuilder {
+        self.client
+            .get(url)
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
+    }
+
+    /// Returns a client that shares the connection pool and the rate limit
+    /// state with this one, but retries requests according to `retry_policy`.
+    ///
+    /// Useful for overriding the retry policy for a single call:
+    ///
+    /// ```rust ,no_run
+    /// # use nekosbest::client::{Client, ClientConfig, RetryPolicy};
+    /// # async fn f(client: &Client) -> Result<(), nekosbest::NekosBestError> {
+    /// let resp = nekosbest::get_with_client(
+    ///     &client.with_retry_policy(RetryPolicy::disabled()),
+    ///     nekosbest::Category::Neko,
+    /// )
+    /// .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_retry_policy(&self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            client: self.client.clone(),
+            client_config: Arc::new(ClientConfig {
+                retry_policy,
+                ..ClientConfig::clone(&self.client_config)
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client that shares the connection pool and the rate limit
+    /// state with this one, but records its requests to, or replays them
+    /// from `cassette`, see [`vcr`](crate::vcr).
+    #[cfg(feature = "vcr")]
+    pub fn with_cassette(&self, cassette: crate::vcr::Cassette) -> Self {
+        Self {
+            cassette: Some(cassette),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client that shares the connection pool and the rate limit
+    /// state with this one, but with a new, empty cache, see [`cache`](crate::cache).
+    pub fn with_cache(&self, config: crate::cache::CacheConfig) -> Self {
+        Self {
+            cache: Some(Arc::new(crate::cache::ResponseCache::new(config))),
+            ..self.clone()
+        }
+    }
+
+    /// The cache of this client, if it has one.
+    pub fn cache(&self) -> Option<&crate::cache::ResponseCache> {
+        self.cache.as_deref()
+    }
+
+    pub(crate) fn cached<T: crate::cache::Cacheable>(&self, key: &str) -> Option<T> {
+        self.cache.as_ref()?.get(key)
+    }
+
+    pub(crate) fn cache_insert<T: crate::cache::Cacheable>(&self, key: &str, value: T) {
+        if let Some(cache) = &self.cache {
+            cache.insert(key, value);
+        }
+    }
+
+    /// Sends the request, retrying it according to the
+    /// [`RetryPolicy`] of this client.
+    ///
+    /// The returned response may still have an error status, if
+    /// it wasn't a retryable one or the attempts ran out.
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub(crate) async fn send(
+        &self,
+        bucket: RatelimitBucket,
+        req: ReqBuilder,
+    ) -> Result<ReqwestResponse, crate::NekosBestError> {
+        let policy = self.client_config.retry_policy;
+        let mut attempt = 1;
+
+        loop {
+            let this_attempt = match req.try_clone() {
+                Some(r) if attempt < policy.max_attempts => r,
+                // out of attempts, or the request cannot be replayed
+                _ => break,
+            };
+
+            let delay = match self.send_once(bucket, this_attempt).await {
+                Ok(resp) if RetryPolicy::should_retry_status(resp.status()) => {
+                    policy.delay(attempt, resp.headers())
+                }
+                Ok(resp) => return Ok(resp),
+                Err(crate::NekosBestError::ReqwestError(e))
+                    if RetryPolicy::should_retry_error(&e) =>
+                {
+                    policy.backoff(attempt)
                 }
+                Err(e) => return Err(e),
+            };
+
+            sleep(delay).await;
+            attempt += 1;
+        }
+
+        self.send_once(bucket, req).await
+    }
+
+    /// Sends the request, keeping track of the rate limit of `bucket`.
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn send_once(
+        &self,
+        bucket: RatelimitBucket,
+        req: ReqBuilder,
+    ) -> Result<ReqwestResponse, crate::NekosBestError> {
+        let req = req.build()?;
+
+        #[cfg(feature = "vcr")]
+        if let Some(cassette) = &self.cassette {
+            if cassette.mode() == crate::vcr::CassetteMode::Replay {
+                return cassette.replay_response(req.method(), req.url());
             }
         }
 
-        Ok(())
+        let permit = self
+            .ratelimits
+            .bucket(bucket)
+            .acquire(self.client_config.ratelimit_behavior(bucket))
+            .await?;
+
+        // the url of the request, not the final one of the response, which
+        // differs after a redirect
+        #[cfg(feature = "vcr")]
+        let (method, url) = (req.method().clone(), req.url().clone());
+
+        let resp = self.client.execute(req).await?;
+
+        permit.update(resp.status(), resp.headers());
+
+        #[cfg(feature = "vcr")]
+        if let Some(cassette) = &self.cassette {
+            return cassette.record_response(&method, &url, resp).await;
+        }
+
+        Ok(resp)
     }
+}
 
-    pub(crate) async fn update_search_ratelimit_data(&self, headers: &reqwest::header::HeaderMap) {
-        let Some(remaining) = headers.get("X-Rate-Limit-Remaining")
-            else { return; };
-        let Some(reset) = headers.get("X-Rate-Limit-Reset")
-            else { return; };
-        let Ok(remaining) = remaining.to_str() else { return; };
-        let Ok(remaining) = remaining.parse::<u32>() else { return; };
-        let Ok(reset) = reset.to_str() else { return; };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnyCategory, Category, BASE_URL};
 
-        let Ok(reset) = reset.parse::<chrono::DateTime::<chrono::Utc>>()
-            else { return; };
+    #[test]
+    fn default_base_url() {
+        assert_eq!(ClientConfig::default().base_url(), BASE_URL);
 
-        let diff = reset - chrono::Utc::now();
+        let config = ClientConfig {
+            api_root: "http://localhost:8080/api/".to_owned(),
+            api_version: 3,
+            ..Default::default()
+        };
+        assert_eq!(config.base_url(), "http://localhost:8080/api/v3");
+    }
 
-        if diff < chrono::Duration::zero() {
-            *self.search_ratelimit_data.lock().await = None;
-            return;
+    #[test]
+    fn endpoint_urls_are_encoded() {
+        let client = Client::new(ClientConfig::default());
+        assert_eq!(client.endpoint_url(Category::Neko), format!("{BASE_URL}/neko"));
+        assert_eq!(
+            client.endpoint_url(AnyCategory::Other("../search?query=x#y".to_owned())),
+            format!("{BASE_URL}/..%2Fsearch%3Fquery=x%23y")
+        );
+        // dot segments are dropped rather than resolved
+        assert_eq!(client.endpoint_url(".."), BASE_URL);
+    }
+
+    #[test]
+    fn retry_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            jitter: false,
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_secs(1));
+        assert_eq!(policy.backoff(100), Duration::from_secs(1));
+
+        let policy = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        for attempt in 1..10 {
+            let backoff = policy.backoff(attempt);
+            let max = RetryPolicy {
+                jitter: false,
+                ..policy
+            }
+            .backoff(attempt);
+            assert!(backoff >= max / 2 && backoff <= max);
         }
 
-        *self.search_ratelimit_data.lock().await = Some(SearchRatelimitData {
-            remaining,
-            resets_at: Instant::from_std(std::time::Instant::now().add(diff.to_std().unwrap())),
-        });
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "86400".parse().unwrap());
+        assert_eq!(policy.delay(1, &headers), Duration::from_secs(1));
+        headers.insert(reqwest::header::RETRY_AFTER, "0".parse().unwrap());
+        assert_eq!(policy.delay(1, &headers), Duration::ZERO);
+    }
+
+    #[test]
+    fn client_builder_rejects_invalid_headers() {
+        let err = Client::builder()
+            .default_header("not a header", "value")
+            .build()
+            .err();
+        assert_eq!(err.unwrap().to_string(), r#"invalid header "not a header""#);
+
+        assert!(Client::builder()
+            .default_header("x-bot", "my-bot")
+            .contact("admin@example.com")
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn default_client_is_shared() {
+        assert!(std::ptr::eq(default_client(), default_client()));
+        assert!(set_default_client(Client::new(ClientConfig::default())).is_err());
     }
 }
@@ -33,7 +33,22 @@ pub enum Details {
     Gif(GifDetails),
 }
 
+/// The kind of [`Details`], see [`Category::details_kind`](crate::Category::details_kind).
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DetailsKind {
+    Image,
+    Gif,
+}
+
 impl Details {
+    /// The kind of these details.
+    pub fn kind(&self) -> DetailsKind {
+        match self {
+            Self::Image(..) => DetailsKind::Image,
+            Self::Gif(..) => DetailsKind::Gif,
+        }
+    }
+
     /// Returns `true` if the details is [`Image`].
     ///
     /// [`Image`]: Details::Image
@@ -49,6 +64,7 @@ impl Details {
         }
     }
 
+    #[allow(clippy::result_large_err)]
     pub fn try_into_image(self) -> Result<ImageDetails, Self> {
         if let Self::Image(v) = self {
             Ok(v)
@@ -72,6 +88,7 @@ impl Details {
         }
     }
 
+    #[allow(clippy::result_large_err)]
     pub fn try_into_gif(self) -> Result<GifDetails, Self> {
         if let Self::Gif(v) = self {
             Ok(v)
@@ -1,9 +1,10 @@
-use crate::client::{Client, ClientConfig};
+use crate::client::{default_client, Client, RatelimitBucket};
 use crate::{NekosBestError, NekosBestResponseSingle};
 use image::AnimationDecoder;
 #[cfg(feature = "blocking")]
 use nb_blocking_util::blocking;
 use reqwest::IntoUrl;
+#[cfg(not(feature = "blocking"))]
 use tokio::io::AsyncWriteExt;
 
 #[derive(Clone)]
@@ -68,7 +69,7 @@ impl GifDownloadResult {
 pub async fn download(
     response: &NekosBestResponseSingle,
 ) -> Result<DownloadResult, NekosBestError> {
-    download_with_client(&Client::new(ClientConfig::default()), response).await
+    download_with_client(default_client(), response).await
 }
 
 /// Downloads the image from the given response using the given client.
@@ -83,7 +84,7 @@ pub async fn download_with_client(
 /// Downloads the image from the given url.
 #[cfg_attr(feature = "blocking", blocking)]
 pub async fn download_from_url(url: impl IntoUrl) -> Result<DownloadResult, NekosBestError> {
-    download_from_url_with_client(&Client::new(ClientConfig::default()), url).await
+    download_from_url_with_client(default_client(), url).await
 }
 
 /// Downloads the image from the given url using the given client.
@@ -92,7 +93,10 @@ pub async fn download_from_url_with_client(
     client: &Client,
     url: impl IntoUrl,
 ) -> Result<DownloadResult, NekosBestError> {
-    let resp = crate::prepare_request(client.client.get(url)).send().await?.error_for_status()?;
+    let resp = client
+        .send(RatelimitBucket::Assets, client.get_request(url))
+        .await?
+        .error_for_status()?;
     let content_type = resp
         .headers()
         .get(reqwest::header::CONTENT_TYPE)
@@ -118,7 +122,7 @@ pub async fn download_to_file(
     file: impl AsRef<std::path::Path>,
 ) -> Result<(), NekosBestError> {
     download_to_file_with_client(
-        &Client::new(ClientConfig::default()),
+        default_client(),
         response,
         file,
     ).await
@@ -143,7 +147,7 @@ pub async fn download_from_url_to_file(
     file: impl AsRef<std::path::Path>,
 ) -> Result<(), NekosBestError> {
     download_from_url_to_file_with_client(
-        &Client::new(ClientConfig::default()),
+        default_client(),
         url,
         file,
     ).await
@@ -157,7 +161,12 @@ pub async fn download_from_url_to_file_with_client(
 ) -> Result<(), NekosBestError> {
     use futures::StreamExt;
 
-    let resp = crate::prepare_request(client.client.get(url)).send().await?.error_for_status()?;
+    // Only sending the request is retried, see `RetryPolicy`: once the
+    // file has been partially written, errors are returned as-is.
+    let resp = client
+        .send(RatelimitBucket::Assets, client.get_request(url))
+        .await?
+        .error_for_status()?;
     let mut stream = resp.bytes_stream();
     let mut f = tokio::fs::File::create(file).await?;
 
@@ -175,12 +184,51 @@ pub fn download_from_url_to_file_with_client(
     url: impl IntoUrl,
     file: impl AsRef<std::path::Path>,
 ) -> Result<(), NekosBestError> {
-    use std::io::Write;
-
-    let mut resp = crate::prepare_request(client.client.get(url)).send()?.error_for_status()?;
+    let mut resp = client
+        .send(RatelimitBucket::Assets, client.get_request(url))?
+        .error_for_status()?;
     let mut f = std::fs::File::create(file)?;
 
     std::io::copy(&mut resp, &mut f)?;
 
     Ok(())
 }
+
+impl Client {
+    /// Downloads the image from the given response, see [`download_with_client`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn download(
+        &self,
+        response: &NekosBestResponseSingle,
+    ) -> Result<DownloadResult, NekosBestError> {
+        download_with_client(self, response).await
+    }
+
+    /// Downloads the image from the given url, see [`download_from_url_with_client`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn download_from_url(&self, url: impl IntoUrl) -> Result<DownloadResult, NekosBestError> {
+        download_from_url_with_client(self, url).await
+    }
+
+    /// Downloads the image from the given response to `file`,
+    /// see [`download_to_file_with_client`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn download_to_file(
+        &self,
+        response: &NekosBestResponseSingle,
+        file: impl AsRef<std::path::Path>,
+    ) -> Result<(), NekosBestError> {
+        download_to_file_with_client(self, response, file).await
+    }
+
+    /// Downloads the image from the given url to `file`,
+    /// see [`download_from_url_to_file_with_client`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn download_from_url_to_file(
+        &self,
+        url: impl IntoUrl,
+        file: impl AsRef<std::path::Path>,
+    ) -> Result<(), NekosBestError> {
+        download_from_url_to_file_with_client(self, url, file).await
+    }
+}
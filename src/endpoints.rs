@@ -0,0 +1,192 @@
+//! Discovery of the endpoints the API serves, through `/endpoints`.
+//!
+//! Comparing them with the categories this crate knows about
+//! ([`Endpoints::drift`]) tells whether it is time to update it.
+
+use std::collections::BTreeMap;
+use std::ops::Deref;
+
+#[cfg(feature = "blocking")]
+use nb_blocking_util::blocking;
+
+use crate::category::Format;
+use crate::client::{default_client, Client, RatelimitBucket};
+use crate::{Category, NekosBestError};
+
+/// The description of an endpoint.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct EndpointDesc {
+    pub format: Format,
+    /// Any other field the server sends.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The endpoints served by the API, by name.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(transparent)]
+pub struct Endpoints(pub BTreeMap<String, EndpointDesc>);
+
+impl Deref for Endpoints {
+    type Target = BTreeMap<String, EndpointDesc>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Endpoints {
+    /// Compares the endpoints with [`Category::ALL_VARIANTS`].
+    pub fn drift(&self) -> DriftReport {
+        let new = self
+            .keys()
+            .filter(|name| Category::from_url_name(name).is_none())
+            .cloned()
+            .collect();
+        let removed = Category::ALL_VARIANTS
+            .iter()
+            .filter(|category| !self.contains_key(category.to_url_name()))
+            .copied()
+            .collect();
+        let changed_format = Category::ALL_VARIANTS
+            .iter()
+            .filter(|category| {
+                self.get(category.to_url_name())
+                    .is_some_and(|desc| desc.format != category.format())
+            })
+            .copied()
+            .collect();
+
+        DriftReport {
+            new,
+            removed,
+            changed_format,
+        }
+    }
+}
+
+/// The differences between the endpoints served by the API
+/// and the categories this crate knows about.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DriftReport {
+    /// Endpoints this crate doesn't know about.
+    pub new: Vec<String>,
+    /// Categories that are not served by the API anymore.
+    pub removed: Vec<Category>,
+    /// Categories whose media format differs from [`Category::format`].
+    pub changed_format: Vec<Category>,
+}
+
+impl DriftReport {
+    /// Returns `true` if the crate is in sync with the API.
+    pub fn is_empty(&self) -> bool {
+        self.new.is_empty() && self.removed.is_empty() && self.changed_format.is_empty()
+    }
+}
+
+impl std::fmt::Display for DriftReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("no drift");
+        }
+
+        fn names(categories: &[Category]) -> String {
+            categories
+                .iter()
+                .map(|category| category.to_url_name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        let mut sep = "";
+        if !self.new.is_empty() {
+            write!(f, "new categories: {}", self.new.join(", "))?;
+            sep = "; ";
+        }
+        if !self.removed.is_empty() {
+            write!(f, "{sep}removed categories: {}", names(&self.removed))?;
+            sep = "; ";
+        }
+        if !self.changed_format.is_empty() {
+            write!(f, "{sep}changed format: {}", names(&self.changed_format))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Gets the endpoints served by the API, with a supplied client.
+///
+/// # Errors
+/// Any errors that can happen, refer to [`NekosBestError`].
+#[cfg_attr(feature = "blocking", blocking)]
+pub async fn get_endpoints_with_client(client: &Client) -> Result<Endpoints, NekosBestError> {
+    if let Some(endpoints) = client.cached::<Endpoints>("endpoints") {
+        return Ok(endpoints);
+    }
+
+    let req = client.get_request(client.endpoint_url("endpoints"));
+
+    let resp = client
+        .send(RatelimitBucket::Category, req)
+        .await?
+        .error_for_status()?;
+
+    let endpoints = resp.json::<Endpoints>().await?;
+    client.cache_insert("endpoints", endpoints.clone());
+
+    Ok(endpoints)
+}
+
+/// Gets the endpoints served by the API, with the default client.
+///
+/// # Errors
+/// Any errors that can happen, refer to [`NekosBestError`].
+#[cfg_attr(feature = "blocking", blocking)]
+pub async fn get_endpoints() -> Result<Endpoints, NekosBestError> {
+    get_endpoints_with_client(default_client()).await
+}
+
+impl Client {
+    /// Gets the endpoints served by the API, see [`get_endpoints_with_client`].
+    ///
+    /// # Errors
+    /// Any errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn endpoints(&self) -> Result<Endpoints, NekosBestError> {
+        get_endpoints_with_client(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints_drift() {
+        let mut endpoints: Endpoints = serde_json::from_str(
+            r#"{"brand-new": {"format": "webp", "min": 1}}"#,
+        )
+        .unwrap();
+        assert_eq!(endpoints["brand-new"].format, Format::Unknown);
+        assert_eq!(endpoints["brand-new"].extra["min"], 1);
+
+        for category in Category::ALL_VARIANTS {
+            if *category != Category::Yeet {
+                endpoints.0.insert(
+                    category.to_string(),
+                    serde_json::from_str(r#"{"format": "gif"}"#).unwrap(),
+                );
+            }
+        }
+        let report = endpoints.drift();
+        assert_eq!(report.new, ["brand-new"]);
+        assert_eq!(report.removed, [Category::Yeet]);
+        assert_eq!(report.changed_format, Category::IMAGE_VARIANTS);
+        assert_eq!(
+            report.to_string(),
+            "new categories: brand-new; removed categories: yeet; \
+             changed format: husbando, kitsune, neko, waifu"
+        );
+    }
+}
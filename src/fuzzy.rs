@@ -0,0 +1,236 @@
+//! Lenient parsing of categories typed by users, like `"thumbs_up"`,
+//! `"High-Five"` or `"hugs"`, with "did you mean" suggestions.
+//!
+//! ```rust
+//! # use nekosbest::Category;
+//! # use nekosbest::fuzzy::CategoryParser;
+//! assert_eq!(Category::parse_lenient("Thumbs_Up"), Ok(Category::ThumbsUp));
+//! assert_eq!(Category::parse_lenient("facepalms"), Ok(Category::Facepalm));
+//!
+//! let err = Category::parse_lenient("slpa").unwrap_err();
+//! assert_eq!(err.suggestions[0], Category::Slap);
+//!
+//! let parser = CategoryParser::new().alias("headpat", Category::Pat);
+//! assert_eq!(parser.parse("head pats"), Ok(Category::Pat));
+//! ```
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::Category;
+
+/// Aliases known by the default [`CategoryParser`], on top of
+/// the url names and the [display names](Category::display_name).
+const BUILTIN_ALIASES: &[(&str, Category)] = &[
+    ("hi5", Category::Highfive),
+    ("shakehands", Category::Handshake),
+    ("thumbup", Category::ThumbsUp),
+];
+
+/// Inputs longer than this get no suggestions, no category name being
+/// anywhere near that long.
+const MAX_SUGGESTED_LEN: usize = 32;
+
+/// The error returned when parsing an unknown category,
+/// with the closest categories, best first.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("no such category: {input:?}{}", did_you_mean(.suggestions))]
+pub struct UnknownCategory {
+    pub input: String,
+    pub suggestions: Vec<Category>,
+}
+
+fn did_you_mean(suggestions: &[Category]) -> String {
+    if suggestions.is_empty() {
+        return String::new();
+    }
+
+    let names = suggestions
+        .iter()
+        .map(|category| category.to_url_name())
+        .collect::<Vec<_>>();
+    format!(", did you mean {}?", names.join(", "))
+}
+
+/// A lenient category parser: ignores case, spaces, `-`, `_` and `.`,
+/// accepts plurals, display names and a configurable alias table.
+#[derive(Debug, Clone)]
+pub struct CategoryParser {
+    aliases: HashMap<String, Category>,
+    max_suggestions: usize,
+}
+
+impl Default for CategoryParser {
+    fn default() -> Self {
+        let mut parser = Self {
+            aliases: HashMap::new(),
+            max_suggestions: 3,
+        };
+        for category in Category::ALL_VARIANTS {
+            parser = parser
+                .alias(category.to_url_name(), *category)
+                .alias(category.display_name(), *category);
+        }
+        for (alias, category) in BUILTIN_ALIASES {
+            parser = parser.alias(alias, *category);
+        }
+        parser
+    }
+}
+
+impl CategoryParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an alias, normalized the same way as the parsed input.
+    pub fn alias(mut self, alias: &str, category: Category) -> Self {
+        self.aliases.insert(normalize(alias), category);
+        self
+    }
+
+    /// The maximum number of suggestions in [`UnknownCategory`], 3 by default.
+    pub fn max_suggestions(mut self, max_suggestions: usize) -> Self {
+        self.max_suggestions = max_suggestions;
+        self
+    }
+
+    /// Parses `input`.
+    ///
+    /// # Errors
+    /// If no category matches, with suggestions.
+    pub fn parse(&self, input: &str) -> Result<Category, UnknownCategory> {
+        let normalized = normalize(input);
+
+        let singular = [
+            Some(normalized.as_str()),
+            normalized.strip_suffix("es"),
+            normalized.strip_suffix('s'),
+        ];
+        if let Some(category) = singular
+            .into_iter()
+            .flatten()
+            .find_map(|name| self.aliases.get(name))
+        {
+            return Ok(*category);
+        }
+
+        Err(UnknownCategory {
+            input: input.to_owned(),
+            suggestions: self.suggest(&normalized),
+        })
+    }
+
+    /// The closest categories to `normalized`, best first.
+    fn suggest(&self, normalized: &str) -> Vec<Category> {
+        let len = normalized.chars().count();
+        if len > MAX_SUGGESTED_LEN {
+            return Vec::new();
+        }
+        // allow roughly one typo every three characters
+        let max_distance = (len / 3).max(1);
+
+        let mut best = HashMap::<Category, usize>::new();
+        for (alias, category) in &self.aliases {
+            // the distance is at least the difference of the lengths
+            if len.abs_diff(alias.chars().count()) > max_distance {
+                continue;
+            }
+            let distance = edit_distance(normalized, alias);
+            if distance <= max_distance {
+                let d = best.entry(*category).or_insert(distance);
+                *d = (*d).min(distance);
+            }
+        }
+
+        let mut suggestions = best.into_iter().collect::<Vec<_>>();
+        suggestions.sort_by_key(|&(category, distance)| (distance, category));
+        suggestions
+            .into_iter()
+            .take(self.max_suggestions)
+            .map(|(category, _)| category)
+            .collect()
+    }
+}
+
+impl Category {
+    /// Parses `input` leniently, see [`CategoryParser`].
+    ///
+    /// # Errors
+    /// If no category matches, with suggestions.
+    pub fn parse_lenient(input: &str) -> Result<Category, UnknownCategory> {
+        static PARSER: OnceLock<CategoryParser> = OnceLock::new();
+
+        PARSER.get_or_init(CategoryParser::default).parse(input)
+    }
+}
+
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_' | '.'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The edit distance between `a` and `b`, counting insertions, deletions,
+/// substitutions and transpositions of adjacent characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    // d[i][j] is the distance between a[..i] and b[..j]
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lenient_category_parsing() {
+        for (input, category) in [
+            ("thumbs_up", Category::ThumbsUp),
+            ("high-five", Category::Highfive),
+            ("Hugs", Category::Hug),
+            ("facepalms", Category::Facepalm),
+            ("punches", Category::Punch),
+            ("Hold hands", Category::Handhold),
+            ("hi5", Category::Highfive),
+        ] {
+            assert_eq!(Category::parse_lenient(input), Ok(category), "{input}");
+        }
+
+        let err = Category::parse_lenient("hugz").unwrap_err();
+        assert_eq!(err.input, "hugz");
+        assert_eq!(err.suggestions[0], Category::Hug);
+        assert!(err.to_string().starts_with(r#"no such category: "hugz", did you mean hug"#));
+        assert!(Category::parse_lenient("xyzzy").unwrap_err().suggestions.is_empty());
+        assert!(Category::parse_lenient(&"hug".repeat(100)).unwrap_err().suggestions.is_empty());
+
+        let parser = CategoryParser::new()
+            .alias("glomp", Category::Cuddle)
+            .max_suggestions(1);
+        assert_eq!(parser.parse("GLOMPS"), Ok(Category::Cuddle));
+        assert_eq!(parser.parse("pta").unwrap_err().suggestions, [Category::Pat]);
+    }
+}
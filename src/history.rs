@@ -0,0 +1,270 @@
+//! Not showing the same image twice in a row: a [`History`] remembers the
+//! last urls it returned per scope (e.g. a guild, a channel or a user),
+//! and fetches again when the API returns one of them.
+//!
+//! The urls are kept in a [`HistoryStore`], either in memory with
+//! [`MemoryHistoryStore`], or in a file with [`FileHistoryStore`],
+//! to survive restarts.
+//!
+//! ```rust ,no_run
+//! # use nekosbest::Category;
+//! # use nekosbest::history::{FileHistoryStore, History};
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), nekosbest::NekosBestError> {
+//! let client = nekosbest::client::Client::new(Default::default());
+//! let store = FileHistoryStore::open("history.json")?;
+//! let history = History::new(client, store, 10);
+//!
+//! // not one of the last 10 hugs of this channel
+//! let hug = history.get("channel:1234", Category::Hug).await?;
+//! println!("{}", hug.url);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+#[cfg(feature = "blocking")]
+use nb_blocking_util::blocking;
+
+use crate::api::NekosBestApi;
+use crate::{Amount, AnyCategory, NekosBestError, NekosBestResponseSingle};
+
+/// Where a [`History`] keeps the recent urls of every scope.
+pub trait HistoryStore: Send + Sync {
+    /// The recent urls of `scope`, oldest first.
+    fn recent(&self, scope: &str) -> Result<Vec<String>, NekosBestError>;
+
+    /// Adds `url` to the recent urls of `scope`, keeping only the last `size`,
+    /// unless it is already one of them.
+    ///
+    /// Returns whether `url` was added. The check and the addition must be
+    /// atomic, so that concurrent calls never both add the same url.
+    fn record_if_absent(&self, scope: &str, url: &str, size: usize)
+        -> Result<bool, NekosBestError>;
+}
+
+impl<S: HistoryStore + ?Sized> HistoryStore for Arc<S> {
+    fn recent(&self, scope: &str) -> Result<Vec<String>, NekosBestError> {
+        (**self).recent(scope)
+    }
+
+    fn record_if_absent(
+        &self,
+        scope: &str,
+        url: &str,
+        size: usize,
+    ) -> Result<bool, NekosBestError> {
+        (**self).record_if_absent(scope, url, size)
+    }
+}
+
+type Scopes = BTreeMap<String, VecDeque<String>>;
+
+fn record_in(scopes: &mut Scopes, scope: &str, url: &str, size: usize) {
+    let recent = scopes.entry(scope.to_owned()).or_default();
+    recent.push_back(url.to_owned());
+    while recent.len() > size {
+        recent.pop_front();
+    }
+    if recent.is_empty() {
+        scopes.remove(scope);
+    }
+}
+
+fn is_recent_in(scopes: &Scopes, scope: &str, url: &str) -> bool {
+    scopes
+        .get(scope)
+        .is_some_and(|recent| recent.iter().any(|recent| recent == url))
+}
+
+fn recent_in(scopes: &Scopes, scope: &str) -> Vec<String> {
+    scopes
+        .get(scope)
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn lock(scopes: &Mutex<Scopes>) -> MutexGuard<'_, Scopes> {
+    scopes.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A [`HistoryStore`] in memory, lost on restart.
+#[derive(Debug, Default)]
+pub struct MemoryHistoryStore {
+    scopes: Mutex<Scopes>,
+}
+
+impl MemoryHistoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl HistoryStore for MemoryHistoryStore {
+    fn recent(&self, scope: &str) -> Result<Vec<String>, NekosBestError> {
+        Ok(recent_in(&lock(&self.scopes), scope))
+    }
+
+    fn record_if_absent(
+        &self,
+        scope: &str,
+        url: &str,
+        size: usize,
+    ) -> Result<bool, NekosBestError> {
+        let mut scopes = lock(&self.scopes);
+        if is_recent_in(&scopes, scope, url) {
+            return Ok(false);
+        }
+
+        record_in(&mut scopes, scope, url, size);
+        Ok(true)
+    }
+}
+
+/// A [`HistoryStore`] in a JSON file, rewritten on every change.
+#[derive(Debug)]
+pub struct FileHistoryStore {
+    path: PathBuf,
+    scopes: Mutex<Scopes>,
+}
+
+impl FileHistoryStore {
+    /// Loads the history at `path`, or starts an empty one if there is no file yet.
+    ///
+    /// # Errors
+    /// If the file cannot be read or is not a valid history.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, NekosBestError> {
+        let path = path.into();
+        let scopes = match std::fs::read(&path) {
+            Ok(json) => serde_json::from_slice(&json)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Scopes::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path,
+            scopes: Mutex::new(scopes),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn write(&self, scopes: &Scopes) -> Result<(), NekosBestError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // write then rename, so that a crash never leaves a truncated file;
+        // `history.json.tmp` rather than `history.tmp`, which could be the
+        // temporary file of another history
+        let mut tmp = OsString::from(self.path.as_os_str());
+        tmp.push(".tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(scopes)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+
+        Ok(())
+    }
+}
+
+impl HistoryStore for FileHistoryStore {
+    fn recent(&self, scope: &str) -> Result<Vec<String>, NekosBestError> {
+        Ok(recent_in(&lock(&self.scopes), scope))
+    }
+
+    fn record_if_absent(
+        &self,
+        scope: &str,
+        url: &str,
+        size: usize,
+    ) -> Result<bool, NekosBestError> {
+        let mut scopes = lock(&self.scopes);
+        if is_recent_in(&scopes, scope, url) {
+            return Ok(false);
+        }
+
+        // only keep the new state once it is written
+        let mut updated = scopes.clone();
+        record_in(&mut updated, scope, url, size);
+        self.write(&updated)?;
+        *scopes = updated;
+
+        Ok(true)
+    }
+}
+
+/// Fetches images without repeating any of the last few urls of a scope.
+pub struct History<A, S = MemoryHistoryStore> {
+    api: A,
+    store: S,
+    size: usize,
+    max_attempts: usize,
+}
+
+impl<A: NekosBestApi, S: HistoryStore> History<A, S> {
+    /// A history of the last `size` urls of every scope.
+    pub fn new(api: A, store: S, size: usize) -> Self {
+        Self {
+            api,
+            store,
+            size,
+            max_attempts: 3,
+        }
+    }
+
+    /// How many times to fetch before giving up, 3 by default.
+    ///
+    /// The first attempt gets a single image, the next ones
+    /// [`Amount::MAX`] images to pick from.
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn api(&self) -> &A {
+        &self.api
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Gets an image that is not one of the recent urls of `scope`,
+    /// and records it.
+    ///
+    /// Concurrent calls for the same scope never return the same url, since
+    /// an image only counts as fresh once [`HistoryStore::record_if_absent`]
+    /// added it.
+    ///
+    /// # Errors
+    /// [`NekosBestError::NoFreshImage`] if only recent urls came back, or any
+    /// errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn get(
+        &self,
+        scope: &str,
+        category: impl Into<AnyCategory>,
+    ) -> Result<NekosBestResponseSingle, NekosBestError> {
+        let category = category.into();
+        let first = self.api.get(category.clone()).await?;
+        let mut candidates = vec![first];
+        let mut attempts = 1;
+        loop {
+            for resp in candidates {
+                if self.store.record_if_absent(scope, &resp.url, self.size)? {
+                    return Ok(resp);
+                }
+            }
+
+            if attempts >= self.max_attempts {
+                return Err(NekosBestError::NoFreshImage);
+            }
+            candidates = self.api.get_amount(category.clone(), Amount::MAX).await?.0;
+            attempts += 1;
+        }
+    }
+}
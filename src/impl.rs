@@ -4,8 +4,8 @@ use serde::Serializer;
 use std::string::FromUtf8Error;
 
 use crate::{
-    details::{GifDetails, ImageDetails},
-    Category, NekosBestError, NekosBestResponse, NekosBestResponseSingle, BASE_URL,
+    details::{Details, GifDetails, ImageDetails},
+    AnyCategory, NekosBestError, NekosBestResponse, NekosBestResponseSingle,
 };
 
 #[cfg(feature = "blocking")]
@@ -15,7 +15,7 @@ use nb_blocking_util::blocking;
 #[path = "strong_types_impl.rs"]
 mod strong_types_impl;
 
-use crate::client::{Client, ClientConfig, ReqBuilder, ReqwestResponse};
+use crate::client::{default_client, Client, RatelimitBucket, ReqBuilder, ReqwestResponse};
 #[cfg(feature = "strong-types")]
 pub use strong_types_impl::{
     get as st_get, get_amount as st_get_amount, get_with_client as st_get_with_client,
@@ -50,11 +50,11 @@ async fn parse_from_response(
 #[cfg_attr(feature = "blocking", blocking)]
 pub async fn get_with_client(
     client: &Client,
-    category: impl Into<Category>,
+    category: impl Into<AnyCategory>,
 ) -> Result<NekosBestResponseSingle, NekosBestError> {
-    let r = crate::prepare_request(client.client.get(format!("{BASE_URL}/{}", category.into())))
-        .send()
-        .await?;
+    let req = client.get_request(client.endpoint_url(category.into()));
+
+    let r = client.send(RatelimitBucket::Category, req).await?;
 
     let mut resp = parse_from_response(r).await?;
     let resp = resp.0.pop().ok_or(NekosBestError::NotFound)?;
@@ -63,20 +63,23 @@ pub async fn get_with_client(
 }
 
 /// Gets `amount` images, with a supplied client.
-/// Note that the server clamps the amount to the 1..=20 range
+/// The amount must be in the 1..=20 range, see [`Amount`](crate::Amount),
+/// and [`get_bulk_with_client`](crate::bulk::get_bulk_with_client) for more.
 ///
 /// # Errors
-/// Any errors that can happen, refer to [`NekosBestError`].
+/// [`NekosBestError::InvalidAmount`] if the amount is out of range, or any
+/// errors that can happen, refer to [`NekosBestError`].
 #[cfg_attr(feature = "blocking", blocking)]
 pub async fn get_with_client_amount(
     client: &Client,
-    category: impl Into<Category>,
+    category: impl Into<AnyCategory>,
     amount: impl Into<u8>,
 ) -> Result<NekosBestResponse, NekosBestError> {
-    let req = crate::prepare_request(client.client.get(format!("{BASE_URL}/{}", category.into())))
-        .query(&[("amount", amount.into())]);
+    let amount = crate::Amount::try_from(amount.into())?;
+    let req = client.get_request(client.endpoint_url(category.into()))
+        .query(&[("amount", amount.get())]);
 
-    let r = req.send().await?;
+    let r = client.send(RatelimitBucket::Category, req).await?;
 
     let v = parse_from_response(r).await?;
 
@@ -88,8 +91,8 @@ pub async fn get_with_client_amount(
 /// # Errors
 /// Any errors that can happen, refer to [`NekosBestError`].
 #[cfg_attr(feature = "blocking", blocking)]
-pub async fn get(category: impl Into<Category>) -> Result<NekosBestResponseSingle, NekosBestError> {
-    get_with_client(&Client::new(ClientConfig::default()), category).await
+pub async fn get(category: impl Into<AnyCategory>) -> Result<NekosBestResponseSingle, NekosBestError> {
+    get_with_client(default_client(), category).await
 }
 
 /// Gets `amount` images, with the default client.
@@ -98,10 +101,10 @@ pub async fn get(category: impl Into<Category>) -> Result<NekosBestResponseSingl
 /// Any errors that can happen, refer to [`NekosBestError`].
 #[cfg_attr(feature = "blocking", blocking)]
 pub async fn get_amount(
-    category: impl Into<Category>,
+    category: impl Into<AnyCategory>,
     amount: impl Into<u8>,
 ) -> Result<NekosBestResponse, NekosBestError> {
-    get_with_client_amount(&Client::new(ClientConfig::default()), category, amount).await
+    get_with_client_amount(default_client(), category, amount).await
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -128,29 +131,59 @@ fn header_deserialize_urlencoded(
     Ok(s)
 }
 
+fn image_details_from_headers(headers: &HeaderMap) -> Result<ImageDetails, NekosBestError> {
+    Ok(ImageDetails {
+        artist_name: header_deserialize_urlencoded(headers, "artist_name")?,
+        artist_href: header_deserialize_urlencoded(headers, "artist_href")?.parse()?,
+        source_url: header_deserialize_urlencoded(headers, "source_url")?.parse()?,
+    })
+}
+
+fn gif_details_from_headers(headers: &HeaderMap) -> Result<GifDetails, NekosBestError> {
+    Ok(GifDetails {
+        anime_name: header_deserialize_urlencoded(headers, "anime_name")?,
+    })
+}
+
+/// Gets the details of an asset, from the cache of the client if
+/// possible, telling images and gifs apart by the headers the server sends.
 #[cfg_attr(feature = "blocking", blocking)]
-pub async fn get_with_client_image_details(
-    client: &Client,
-    url: impl IntoUrl,
-) -> Result<ImageDetails, NekosBestError> {
-    let resp = crate::prepare_request(client.client.get(url))
-        .send()
+async fn get_cached_details(client: &Client, url: impl IntoUrl) -> Result<Details, NekosBestError> {
+    let url = client.get_request(url).build()?.url().clone();
+    if let Some(details) = client.cached::<Details>(url.as_str()) {
+        return Ok(details);
+    }
+
+    let resp = client
+        .send(RatelimitBucket::Assets, client.get_request(url.clone()))
         .await?
         .error_for_status()?;
     let headers = resp.headers();
 
-    let details = ImageDetails {
-        artist_name: header_deserialize_urlencoded(headers, "artist_name")?,
-        artist_href: header_deserialize_urlencoded(headers, "artist_href")?.parse()?,
-        source_url: header_deserialize_urlencoded(headers, "source_url")?.parse()?,
+    let details = if headers.contains_key("artist_name") {
+        Details::Image(image_details_from_headers(headers)?)
+    } else {
+        Details::Gif(gif_details_from_headers(headers)?)
     };
+    client.cache_insert(url.as_str(), details.clone());
 
     Ok(details)
 }
 
+#[cfg_attr(feature = "blocking", blocking)]
+pub async fn get_with_client_image_details(
+    client: &Client,
+    url: impl IntoUrl,
+) -> Result<ImageDetails, NekosBestError> {
+    match get_cached_details(client, url).await? {
+        Details::Image(details) => Ok(details),
+        _ => Err(HeaderDeserializeUrlEncodedError::MissingHeader.into()),
+    }
+}
+
 #[cfg_attr(feature = "blocking", blocking)]
 pub async fn get_image_details(url: impl IntoUrl) -> Result<ImageDetails, NekosBestError> {
-    get_with_client_image_details(&Client::new(ClientConfig::default()), url).await
+    get_with_client_image_details(default_client(), url).await
 }
 
 #[cfg_attr(feature = "blocking", blocking)]
@@ -158,31 +191,46 @@ pub async fn get_with_client_gif_details(
     client: &Client,
     url: impl IntoUrl,
 ) -> Result<GifDetails, NekosBestError> {
-    let resp = crate::prepare_request(client.client.get(url))
-        .send()
-        .await?
-        .error_for_status()?;
-    let headers = resp.headers();
+    match get_cached_details(client, url).await? {
+        Details::Gif(details) => Ok(details),
+        _ => Err(HeaderDeserializeUrlEncodedError::MissingHeader.into()),
+    }
+}
 
-    let details = GifDetails {
-        anime_name: header_deserialize_urlencoded(headers, "anime_name")?,
-    };
+#[cfg_attr(feature = "blocking", blocking)]
+pub async fn get_gif_details(url: impl IntoUrl) -> Result<GifDetails, NekosBestError> {
+    get_with_client_gif_details(default_client(), url).await
+}
 
-    Ok(details)
+/// Gets the details of an image or a gif, with a supplied client,
+/// telling them apart by the headers the server sends.
+///
+/// # Errors
+/// Any errors that can happen, refer to [`NekosBestError`].
+#[cfg_attr(feature = "blocking", blocking)]
+pub async fn get_with_client_details(
+    client: &Client,
+    url: impl IntoUrl,
+) -> Result<Details, NekosBestError> {
+    get_cached_details(client, url).await
 }
 
+/// Gets the details of an image or a gif, with the default client.
+///
+/// # Errors
+/// Any errors that can happen, refer to [`NekosBestError`].
 #[cfg_attr(feature = "blocking", blocking)]
-pub async fn get_gif_details(url: impl IntoUrl) -> Result<GifDetails, NekosBestError> {
-    get_with_client_gif_details(&Client::new(ClientConfig::default()), url).await
+pub async fn get_details(url: impl IntoUrl) -> Result<Details, NekosBestError> {
+    get_with_client_details(default_client(), url).await
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct SearchQuery {
     query: String,
     #[serde(rename = "type")]
     kind: SearchQueryKind,
     #[serde(skip_serializing_if = "Option::is_none")]
-    category: Option<Category>,
+    category: Option<AnyCategory>,
     #[serde(skip_serializing_if = "Option::is_none")]
     amount: Option<usize>,
 }
@@ -197,7 +245,7 @@ impl SearchQuery {
         }
     }
 
-    pub fn category(mut self, category: impl Into<Category>) -> Self {
+    pub fn category(mut self, category: impl Into<AnyCategory>) -> Self {
         self.category = Some(category.into());
         self
     }
@@ -212,7 +260,7 @@ impl SearchQuery {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(u32)]
 pub enum SearchQueryKind {
     Image = 1,
@@ -228,31 +276,100 @@ impl serde::Serialize for SearchQueryKind {
     }
 }
 
+/// Searches, with a supplied client.
+///
+/// # Errors
+/// Any errors that can happen, refer to [`NekosBestError`].
 #[cfg_attr(feature = "blocking", blocking)]
 pub async fn search_with_client(
     client: &Client,
     query: SearchQuery,
 ) -> Result<NekosBestResponse, NekosBestError> {
-    let req = crate::prepare_request(client.client.get(format!("{BASE_URL}/search")));
+    let key = serde_json::to_string(&query)?;
+    if let Some(resp) = client.cached::<NekosBestResponse>(&key) {
+        return Ok(resp);
+    }
 
-    #[cfg(not(feature = "blocking"))]
-    client.handle_search_ratelimit().await?;
+    let req = client.get_request(client.endpoint_url("search"));
 
     let req = query.apply_to(req);
 
-    let res = req.send().await?;
+    let res = client.send(RatelimitBucket::Search, req).await?;
 
-    #[cfg(not(feature = "blocking"))]
-    client.update_search_ratelimit_data(res.headers()).await;
+    let resp = parse_from_response(res).await?;
+    client.cache_insert(&key, resp.clone());
 
-    Ok(parse_from_response(res).await?)
+    Ok(resp)
 }
 
-#[deprecated(
-    note = "Use `search_with_client` instead, and provide a client.",
-    since = "0.17.0"
-)]
+/// Searches, with the default client.
+///
+/// # Errors
+/// Any errors that can happen, refer to [`NekosBestError`].
 #[cfg_attr(feature = "blocking", blocking)]
 pub async fn search(query: SearchQuery) -> Result<NekosBestResponse, NekosBestError> {
-    search_with_client(&Client::new(ClientConfig::default()), query).await
+    search_with_client(default_client(), query).await
+}
+
+impl Client {
+    /// Gets a single image, see [`get_with_client`].
+    ///
+    /// # Errors
+    /// Any errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn get(
+        &self,
+        category: impl Into<AnyCategory>,
+    ) -> Result<NekosBestResponseSingle, NekosBestError> {
+        get_with_client(self, category).await
+    }
+
+    /// Gets `amount` images, see [`get_with_client_amount`].
+    ///
+    /// # Errors
+    /// Any errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn get_many(
+        &self,
+        category: impl Into<AnyCategory>,
+        amount: impl Into<u8>,
+    ) -> Result<NekosBestResponse, NekosBestError> {
+        get_with_client_amount(self, category, amount).await
+    }
+
+    /// Searches, see [`search_with_client`].
+    ///
+    /// # Errors
+    /// Any errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn search(&self, query: SearchQuery) -> Result<NekosBestResponse, NekosBestError> {
+        search_with_client(self, query).await
+    }
+
+    /// Gets the details of an image or a gif, see [`get_with_client_details`].
+    ///
+    /// # Errors
+    /// Any errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn details(&self, url: impl IntoUrl) -> Result<Details, NekosBestError> {
+        get_with_client_details(self, url).await
+    }
+
+    /// Gets the details of an image, see [`get_with_client_image_details`].
+    ///
+    /// # Errors
+    /// Any errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn image_details(&self, url: impl IntoUrl) -> Result<ImageDetails, NekosBestError> {
+        get_with_client_image_details(self, url).await
+    }
+
+    /// Gets the details of a gif, see [`get_with_client_gif_details`].
+    ///
+    /// # Errors
+    /// Any errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn gif_details(&self, url: impl IntoUrl) -> Result<GifDetails, NekosBestError> {
+        get_with_client_gif_details(self, url).await
+    }
 }
@@ -0,0 +1,257 @@
+//! A local, deterministic classifier mapping free text and emoji, like
+//! `"I'm so sad"`, `"lol"` or `"👋"`, to the categories that fit them.
+//!
+//! It only looks up words and emoji in a lexicon, which can be extended.
+//!
+//! ```rust
+//! # use nekosbest::Category;
+//! # use nekosbest::intent::IntentClassifier;
+//! let classifier = IntentClassifier::new();
+//! assert_eq!(classifier.best("I'm so sad"), Some(Category::Cry));
+//! assert_eq!(classifier.best("lol"), Some(Category::Laugh));
+//! assert_eq!(classifier.best("👋"), Some(Category::Wave));
+//!
+//! let classifier = classifier.word("glomp", Category::Cuddle, 2);
+//! assert_eq!(classifier.best("*glomps you*"), Some(Category::Cuddle));
+//! ```
+
+use std::collections::HashMap;
+
+use crate::Category;
+
+/// The weight of the url names of the categories.
+const NAME_WEIGHT: u32 = 2;
+/// The weight of the [emoji](Category::emoji) of the categories.
+const EMOJI_WEIGHT: u32 = 3;
+
+/// Words and emoji known by the default [`IntentClassifier`], on top of the
+/// url names and emoji of the categories, with their weight.
+const BUILTIN_LEXICON: &[(&str, Category, u32)] = &[
+    ("mad", Category::Angry, 2),
+    ("furious", Category::Angry, 2),
+    ("😡", Category::Angry, 3),
+    ("idiot", Category::Baka, 2),
+    ("stupid", Category::Baka, 2),
+    ("dummy", Category::Baka, 2),
+    ("embarrassed", Category::Blush, 2),
+    ("☺️", Category::Blush, 2),
+    ("boring", Category::Bored, 2),
+    ("sad", Category::Cry, 2),
+    ("sob", Category::Cry, 2),
+    ("tears", Category::Cry, 2),
+    ("😭", Category::Cry, 3),
+    ("🥲", Category::Cry, 2),
+    ("snuggle", Category::Cuddle, 2),
+    ("party", Category::Dance, 1),
+    ("🕺", Category::Dance, 3),
+    ("ugh", Category::Facepalm, 1),
+    ("smh", Category::Facepalm, 2),
+    ("🤦‍♂️", Category::Facepalm, 3),
+    ("🤦‍♀️", Category::Facepalm, 3),
+    ("yay", Category::Happy, 2),
+    ("glad", Category::Happy, 2),
+    ("😁", Category::Happy, 2),
+    ("hug", Category::Hug, 2),
+    ("love", Category::Hug, 1),
+    ("❤️", Category::Hug, 1),
+    ("kiss", Category::Kiss, 2),
+    ("😚", Category::Kiss, 2),
+    ("lol", Category::Laugh, 2),
+    ("lmao", Category::Laugh, 2),
+    ("haha", Category::Laugh, 2),
+    ("rofl", Category::Laugh, 2),
+    ("🤣", Category::Laugh, 3),
+    ("yes", Category::Nod, 1),
+    ("agree", Category::Nod, 2),
+    ("hungry", Category::Nom, 2),
+    ("food", Category::Nom, 1),
+    ("eat", Category::Nom, 2),
+    ("no", Category::Nope, 1),
+    ("nah", Category::Nope, 2),
+    ("headpat", Category::Pat, 2),
+    ("sulk", Category::Pout, 2),
+    ("hmph", Category::Pout, 2),
+    ("bye", Category::Run, 1),
+    ("flee", Category::Run, 2),
+    ("idk", Category::Shrug, 2),
+    ("dunno", Category::Shrug, 2),
+    ("whatever", Category::Shrug, 1),
+    ("🤷‍♂️", Category::Shrug, 3),
+    ("🤷‍♀️", Category::Shrug, 3),
+    ("tired", Category::Sleep, 1),
+    ("sleepy", Category::Sleep, 2),
+    ("goodnight", Category::Sleep, 2),
+    ("gn", Category::Sleep, 2),
+    ("🙂", Category::Smile, 2),
+    ("hmm", Category::Think, 2),
+    ("wonder", Category::Think, 1),
+    ("ok", Category::ThumbsUp, 1),
+    ("okay", Category::ThumbsUp, 1),
+    ("nice", Category::ThumbsUp, 1),
+    ("thanks", Category::ThumbsUp, 1),
+    ("hi", Category::Wave, 2),
+    ("hello", Category::Wave, 2),
+    ("hey", Category::Wave, 1),
+    ("bye", Category::Wave, 2),
+    ("cya", Category::Wave, 2),
+    ("😜", Category::Wink, 2),
+    ("😪", Category::Yawn, 2),
+    ("throw", Category::Yeet, 1),
+];
+
+/// A category matched by [`IntentClassifier::classify`], with its score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntentMatch {
+    pub category: Category,
+    pub score: u32,
+}
+
+/// Maps free text and emoji to categories, with a lexicon of weighted
+/// words and emoji.
+///
+/// Words are matched case-insensitively, also without an `s`, `es`,
+/// `ed` or `ing` suffix. Emoji are matched anywhere in the text.
+#[derive(Debug, Clone)]
+pub struct IntentClassifier {
+    words: HashMap<String, Vec<(Category, u32)>>,
+    emoji: Vec<(String, Category, u32)>,
+}
+
+impl Default for IntentClassifier {
+    fn default() -> Self {
+        let mut classifier = Self {
+            words: HashMap::new(),
+            emoji: Vec::new(),
+        };
+        for category in Category::ALL_VARIANTS {
+            classifier = classifier
+                .word(category.to_url_name(), *category, NAME_WEIGHT)
+                .emoji(category.emoji(), *category, EMOJI_WEIGHT);
+        }
+        for (entry, category, weight) in BUILTIN_LEXICON {
+            classifier = if entry.chars().any(char::is_alphanumeric) {
+                classifier.word(entry, *category, *weight)
+            } else {
+                classifier.emoji(entry, *category, *weight)
+            };
+        }
+        classifier
+    }
+}
+
+impl IntentClassifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `word` to the lexicon. Matching it adds `weight` to the score of `category`.
+    pub fn word(mut self, word: &str, category: Category, weight: u32) -> Self {
+        let entries = self.words.entry(word.to_lowercase()).or_default();
+        match entries.iter_mut().find(|(c, _)| *c == category) {
+            Some((_, w)) => *w = (*w).max(weight),
+            None => entries.push((category, weight)),
+        }
+        self
+    }
+
+    /// Adds `emoji` (or any other sequence of symbols) to the lexicon.
+    /// Matching it adds `weight` to the score of `category`.
+    pub fn emoji(mut self, emoji: &str, category: Category, weight: u32) -> Self {
+        self.emoji.push((emoji.to_owned(), category, weight));
+        self
+    }
+
+    /// The categories matching `text`, best first.
+    pub fn classify(&self, text: &str) -> Vec<IntentMatch> {
+        let mut scores = HashMap::<Category, u32>::new();
+
+        for word in words(text) {
+            let stems = [
+                Some(word.as_str()),
+                word.strip_suffix('s'),
+                word.strip_suffix("es"),
+                word.strip_suffix("ed"),
+                word.strip_suffix("ing"),
+            ];
+            if let Some(entries) = stems
+                .into_iter()
+                .flatten()
+                .find_map(|stem| self.words.get(stem))
+            {
+                for (category, weight) in entries {
+                    let score = scores.entry(*category).or_default();
+                    *score = score.saturating_add(*weight);
+                }
+            }
+        }
+
+        for (emoji, category, weight) in &self.emoji {
+            let count = text.matches(emoji.as_str()).count();
+            if count > 0 {
+                let count = u32::try_from(count).unwrap_or(u32::MAX);
+                let score = scores.entry(*category).or_default();
+                *score = score.saturating_add(weight.saturating_mul(count));
+            }
+        }
+
+        let mut matches = scores
+            .into_iter()
+            .map(|(category, score)| IntentMatch { category, score })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|m| (std::cmp::Reverse(m.score), m.category));
+        matches
+    }
+
+    /// The best category for `text`, if any matches.
+    pub fn best(&self, text: &str) -> Option<Category> {
+        self.classify(text).first().map(|m| m.category)
+    }
+}
+
+/// The lowercase words of `text`.
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intent_classification() {
+        let classifier = IntentClassifier::new();
+        for (text, category) in [
+            ("I'm so sad", Category::Cry),
+            ("crying rn", Category::Cry),
+            ("LOL", Category::Laugh),
+            ("👋", Category::Wave),
+            ("🤦", Category::Facepalm),
+            ("🤦‍♀️ seriously", Category::Facepalm),
+            ("hugs!!", Category::Hug),
+            ("idk 🤷", Category::Shrug),
+        ] {
+            assert_eq!(classifier.best(text), Some(category), "{text}");
+        }
+        assert_eq!(classifier.best("the weather"), None);
+
+        // ties are broken by category order, so results are deterministic
+        assert_eq!(
+            classifier.classify("hi, see you, bye"),
+            [
+                IntentMatch {
+                    category: Category::Wave,
+                    score: 4
+                },
+                IntentMatch {
+                    category: Category::Run,
+                    score: 1
+                },
+            ]
+        );
+
+        let classifier = classifier.word("zoomies", Category::Run, 5);
+        assert_eq!(classifier.best("lol zoomies"), Some(Category::Run));
+    }
+}
@@ -0,0 +1,247 @@
+//! Whether a category is an interaction between two users or a solo
+//! reaction, and the sentences bots usually send along the gif,
+//! like "Senko hugs Nakano".
+//!
+//! ```rust
+//! # use nekosbest::Category;
+//! # use nekosbest::interaction::{InteractionKind, Phrasebook};
+//! assert_eq!(Category::Hug.interaction_kind(), InteractionKind::Interaction);
+//!
+//! let mut phrases = Phrasebook::new();
+//! assert_eq!(phrases.render("en", Category::Hug, "Senko", "Nakano"), "Senko hugs Nakano");
+//!
+//! phrases.set("de", Category::Hug, "{actor} umarmt {target}");
+//! assert_eq!(phrases.render("de", Category::Hug, "Senko", "Nakano"), "Senko umarmt Nakano");
+//! // falls back to English
+//! assert_eq!(phrases.render("de", Category::Cry, "Senko", "Nakano"), "Senko cries");
+//! ```
+
+use std::collections::HashMap;
+
+use crate::Category;
+
+/// The locale of the built-in phrases.
+pub const DEFAULT_LOCALE: &str = "en";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InteractionKind {
+    /// Someone does something to someone else, like [`Category::Hug`].
+    Interaction,
+    /// Someone reacts on their own, like [`Category::Cry`].
+    Reaction,
+    /// Not an action, but a picture, like [`Category::Neko`].
+    Image,
+}
+
+impl InteractionKind {
+    /// Returns `true` if the category targets another user.
+    pub fn is_targeted(self) -> bool {
+        self == InteractionKind::Interaction
+    }
+}
+
+impl Category {
+    /// Whether this category targets another user, see [`InteractionKind`].
+    pub const fn interaction_kind(self) -> InteractionKind {
+        use InteractionKind::*;
+
+        match self {
+            Category::Baka
+            | Category::Bite
+            | Category::Cuddle
+            | Category::Feed
+            | Category::Handhold
+            | Category::Handshake
+            | Category::Highfive
+            | Category::Hug
+            | Category::Kick
+            | Category::Kiss
+            | Category::Pat
+            | Category::Peck
+            | Category::Poke
+            | Category::Punch
+            | Category::Shoot
+            | Category::Slap
+            | Category::Stare
+            | Category::Tickle
+            | Category::Wave
+            | Category::Wink
+            | Category::Yeet => Interaction,
+            Category::Angry
+            | Category::Blush
+            | Category::Bored
+            | Category::Cry
+            | Category::Dance
+            | Category::Facepalm
+            | Category::Happy
+            | Category::Laugh
+            | Category::Lurk
+            | Category::Nod
+            | Category::Nom
+            | Category::Nope
+            | Category::Pout
+            | Category::Run
+            | Category::Shrug
+            | Category::Sleep
+            | Category::Smile
+            | Category::Smug
+            | Category::Think
+            | Category::ThumbsUp
+            | Category::Yawn => Reaction,
+            Category::Husbando | Category::Kitsune | Category::Neko | Category::Waifu => Image,
+        }
+    }
+
+    /// The built-in English phrase template of this category,
+    /// see [`Phrasebook`].
+    pub const fn phrase_template(self) -> &'static str {
+        match self {
+            Category::Angry => "{actor} is angry",
+            Category::Baka => "{actor} calls {target} a baka",
+            Category::Bite => "{actor} bites {target}",
+            Category::Blush => "{actor} blushes",
+            Category::Bored => "{actor} is bored",
+            Category::Cry => "{actor} cries",
+            Category::Cuddle => "{actor} cuddles {target}",
+            Category::Dance => "{actor} dances",
+            Category::Facepalm => "{actor} facepalms",
+            Category::Feed => "{actor} feeds {target}",
+            Category::Handhold => "{actor} holds hands with {target}",
+            Category::Handshake => "{actor} shakes hands with {target}",
+            Category::Happy => "{actor} is happy",
+            Category::Highfive => "{actor} high-fives {target}",
+            Category::Hug => "{actor} hugs {target}",
+            Category::Husbando => "{actor} shares a husbando",
+            Category::Kick => "{actor} kicks {target}",
+            Category::Kiss => "{actor} kisses {target}",
+            Category::Kitsune => "{actor} shares a kitsune",
+            Category::Laugh => "{actor} laughs",
+            Category::Lurk => "{actor} lurks",
+            Category::Neko => "{actor} shares a neko",
+            Category::Nod => "{actor} nods",
+            Category::Nom => "{actor} noms",
+            Category::Nope => "{actor} says nope",
+            Category::Pat => "{actor} pats {target}",
+            Category::Peck => "{actor} gives {target} a peck",
+            Category::Poke => "{actor} pokes {target}",
+            Category::Pout => "{actor} pouts",
+            Category::Punch => "{actor} punches {target}",
+            Category::Run => "{actor} runs away",
+            Category::Shoot => "{actor} shoots {target}",
+            Category::Shrug => "{actor} shrugs",
+            Category::Slap => "{actor} slaps {target}",
+            Category::Sleep => "{actor} falls asleep",
+            Category::Smile => "{actor} smiles",
+            Category::Smug => "{actor} looks smug",
+            Category::Stare => "{actor} stares at {target}",
+            Category::Think => "{actor} is thinking",
+            Category::ThumbsUp => "{actor} gives a thumbs up",
+            Category::Tickle => "{actor} tickles {target}",
+            Category::Waifu => "{actor} shares a waifu",
+            Category::Wave => "{actor} waves at {target}",
+            Category::Wink => "{actor} winks at {target}",
+            Category::Yawn => "{actor} yawns",
+            Category::Yeet => "{actor} yeets {target}",
+        }
+    }
+}
+
+/// Phrase templates per locale, falling back to the built-in
+/// English ones ([`Category::phrase_template`]).
+///
+/// Templates may contain the `{actor}` and `{target}` placeholders.
+#[derive(Debug, Clone, Default)]
+pub struct Phrasebook {
+    locales: HashMap<String, HashMap<Category, String>>,
+}
+
+impl Phrasebook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the template of `category` for `locale`, overriding
+    /// the built-in one when `locale` is [`DEFAULT_LOCALE`].
+    pub fn set(
+        &mut self,
+        locale: impl Into<String>,
+        category: Category,
+        template: impl Into<String>,
+    ) -> &mut Self {
+        self.locales
+            .entry(locale.into())
+            .or_default()
+            .insert(category, template.into());
+        self
+    }
+
+    /// The template of `category` for `locale`.
+    pub fn template(&self, locale: &str, category: Category) -> &str {
+        [locale, DEFAULT_LOCALE]
+            .iter()
+            .find_map(|locale| self.locales.get(*locale)?.get(&category))
+            .map_or(category.phrase_template(), String::as_str)
+    }
+
+    /// Renders the template of `category` for `locale`.
+    ///
+    /// `target` is only used by the templates of the
+    /// [interactions](InteractionKind::Interaction).
+    pub fn render(&self, locale: &str, category: Category, actor: &str, target: &str) -> String {
+        let mut rendered = String::new();
+        let mut rest = self.template(locale, category);
+
+        // a single pass, so that placeholders in the names are left alone
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            if let Some(after) = rest.strip_prefix("{actor}") {
+                rendered.push_str(actor);
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("{target}") {
+                rendered.push_str(target);
+                rest = after;
+            } else {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+        rendered.push_str(rest);
+
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interaction_phrases() {
+        for category in Category::ALL_VARIANTS {
+            let template = category.phrase_template();
+            assert_eq!(
+                template.contains("{target}"),
+                category.interaction_kind().is_targeted(),
+                "{category}"
+            );
+        }
+        assert_eq!(Category::Cry.interaction_kind(), InteractionKind::Reaction);
+        assert_eq!(Category::Neko.interaction_kind(), InteractionKind::Image);
+
+        let mut phrases = Phrasebook::new();
+        phrases
+            .set("en", Category::Pat, "{actor} gives {target} headpats")
+            .set("fr", Category::Hug, "{actor} fait un câlin à {target}");
+        assert_eq!(
+            phrases.render("fr", Category::Pat, "{target}", "Nakano"),
+            "{target} gives Nakano headpats"
+        );
+        assert_eq!(
+            phrases.render("fr", Category::Hug, "Senko", "Nakano"),
+            "Senko fait un câlin à Nakano"
+        );
+        assert_eq!(phrases.template("it", Category::Slap), "{actor} slaps {target}");
+    }
+}
@@ -5,19 +5,46 @@ pub extern crate reqwest;
 #[cfg(feature = "metrics")]
 pub mod metrics;
 
+pub mod api;
+pub mod bulk;
+pub mod cache;
 pub mod category;
+pub mod category_set;
 pub mod client;
 pub mod details;
 #[cfg(feature = "download")]
 pub mod download;
+pub mod endpoints;
+pub mod fuzzy;
+pub mod history;
+pub mod intent;
+pub mod interaction;
+pub mod names;
+#[cfg(all(feature = "prefetch", not(feature = "blocking")))]
+pub mod prefetch;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod response;
-
-pub use category::Category;
+#[cfg(not(feature = "blocking"))]
+pub mod stream;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "vcr")]
+pub mod vcr;
+
+pub use category::{AnyCategory, Category};
+pub use bulk::Amount;
+pub use category_set::CategorySet;
+#[cfg(not(feature = "blocking"))]
+pub use stream::{stream, stream_with_client};
+#[cfg(all(feature = "strong-types", not(feature = "blocking")))]
+pub use stream::{st_stream, st_stream_with_client};
 use url::ParseError;
 
 pub use response::{NekosBestResponse, NekosBestResponseSingle};
 
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum NekosBestError {
     #[error("reqwest error")]
     ReqwestError(#[from] reqwest::Error),
@@ -46,11 +73,31 @@ pub enum NekosBestError {
 
     #[error("rate limited")]
     RateLimited,
+
+    #[error("invalid header {0:?}")]
+    InvalidHeader(String),
+
+    #[error("nothing to choose from")]
+    EmptyChoice,
+
+    #[error("invalid amount")]
+    InvalidAmount(#[from] bulk::InvalidAmount),
+
+    #[error("cannot get {0} images at once, at most {max}", max = bulk::MAX_BULK_COUNT)]
+    BulkTooLarge(usize),
+
+    #[error("no image that was not seen recently")]
+    NoFreshImage,
+
+    #[cfg(feature = "vcr")]
+    #[error("cassette error")]
+    Cassette(#[from] vcr::CassetteError),
 }
 
 pub const API_VERSION: usize = 2;
+pub const API_ROOT: &str = "https://nekos.best/api";
 pub const BASE_URL: &str = "https://nekos.best/api/v2";
-const API_CLIENT_AGENT: &str = concat!(
+pub(crate) const API_CLIENT_AGENT: &str = concat!(
     "Cthulhu/",
     env!("CARGO_PKG_VERSION"),
     " (Unholy Terrors; Devouring Souls)"
@@ -67,24 +114,11 @@ mod implementation;
 
 pub use implementation::*;
 
-mod prep_req {
-    #[cfg(feature = "blocking")]
-    type Rb = reqwest::blocking::RequestBuilder;
-
-    #[cfg(not(feature = "blocking"))]
-    type Rb = reqwest::RequestBuilder;
+mod ratelimit;
 
-    pub fn prepare_request(r: Rb) -> Rb {
-        r.header(reqwest::header::USER_AGENT, crate::API_CLIENT_AGENT)
-    }
-}
-
-use prep_req::prepare_request;
-
-#[cfg(test)]
+#[cfg(all(test, not(feature = "blocking")))]
 mod test {
     use crate::client::{Client, ClientConfig};
-    use std::collections::HashMap;
 
     use super::*;
 
@@ -109,35 +143,15 @@ mod test {
 
     #[tokio::test]
     async fn no_new_endpoints() {
-        let client = reqwest::Client::new();
-
-        async fn get_endpoints(client: &reqwest::Client) -> HashMap<String, EndpointDesc> {
-            crate::prepare_request(client.get(format!("{BASE_URL}/endpoints")))
-                .send()
-                .await
-                .unwrap()
-                .json()
-                .await
-                .unwrap()
-        }
-
-        #[derive(serde::Deserialize)]
-        #[allow(dead_code)]
-        struct EndpointDesc {
-            format: String,
-        }
-
-        let endpoints = get_endpoints(&client).await;
-        let list = endpoints.keys();
-
-        let mut unknown_endpoints = vec![];
-        for item in list {
-            if item.as_str().parse::<Category>().is_err() {
-                unknown_endpoints.push(format!("{BASE_URL}/{item}"));
-            }
-        }
+        let client = Client::new(ClientConfig::default());
 
-        if !unknown_endpoints.is_empty() {
+        let report = client.endpoints().await.unwrap().drift();
+        if !report.new.is_empty() {
+            let unknown_endpoints = report
+                .new
+                .iter()
+                .map(|item| client.endpoint_url(item))
+                .collect::<Vec<_>>();
             panic!("Looks like there are new endpoints, please add them: {unknown_endpoints:?}");
         }
     }
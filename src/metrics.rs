@@ -1,7 +1,11 @@
 use std::str::FromStr;
 use std::{collections::HashMap, convert::TryFrom};
 
-use crate::{category::NoSuchVariant, Category, NekosBestError, BASE_URL};
+#[cfg(feature = "blocking")]
+use nb_blocking_util::blocking;
+
+use crate::client::{default_client, Client, RatelimitBucket};
+use crate::{category::NoSuchVariant, Category, NekosBestError};
 
 #[derive(serde::Deserialize)]
 struct MetricsInternal {
@@ -54,10 +58,12 @@ impl TryFrom<MetricsInternal> for Metrics {
     }
 }
 
-#[derive(Copy, Clone, Hash, Debug, PartialEq, Eq)]
+#[derive(Clone, Hash, Debug, PartialEq, Eq)]
 pub enum CategoryOrTotal {
     Category(Category),
     Total,
+    /// A category this version of the crate doesn't know about.
+    Other(String),
 }
 
 impl FromStr for CategoryOrTotal {
@@ -66,7 +72,10 @@ impl FromStr for CategoryOrTotal {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "total" => Ok(CategoryOrTotal::Total),
-            _ => Ok(CategoryOrTotal::Category(s.parse()?)),
+            _ => Ok(match s.parse() {
+                Ok(category) => CategoryOrTotal::Category(category),
+                Err(NoSuchVariant) => CategoryOrTotal::Other(s.to_owned()),
+            }),
         }
     }
 }
@@ -80,14 +89,51 @@ pub struct Metrics {
     pub reset_in: std::time::Duration,
 }
 
-pub async fn get_metrics_with_client(client: &reqwest::Client) -> Result<Metrics, NekosBestError> {
-    let resp = client.get(format!("{BASE_URL}/metrics")).send().await?;
+#[cfg_attr(feature = "blocking", blocking)]
+pub async fn get_metrics_with_client(client: &Client) -> Result<Metrics, NekosBestError> {
+    let req = client.get_request(client.endpoint_url("metrics"));
+
+    let resp = client.send(RatelimitBucket::Metrics, req).await?.error_for_status()?;
 
     let metrics = resp.json::<Metrics>().await?;
 
     Ok(metrics)
 }
 
+#[cfg_attr(feature = "blocking", blocking)]
 pub async fn get_metrics() -> Result<Metrics, NekosBestError> {
-    get_metrics_with_client(&reqwest::Client::new()).await
+    get_metrics_with_client(default_client()).await
+}
+
+impl Client {
+    /// Gets the metrics, see [`get_metrics_with_client`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn metrics(&self) -> Result<Metrics, NekosBestError> {
+        get_metrics_with_client(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn metrics_with_unknown_categories() {
+        let metrics: Metrics = serde_json::from_str(
+            r#"{
+                "per_id": {"neko": "1", "brand-new": "2", "total": "3"},
+                "per_random": {},
+                "total": "3",
+                "reset_in": "1h"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(metrics.per_id[&CategoryOrTotal::Category(Category::Neko)], 1);
+        assert_eq!(
+            metrics.per_id[&CategoryOrTotal::Other("brand-new".to_owned())],
+            2
+        );
+        assert_eq!(metrics.per_id[&CategoryOrTotal::Total], 3);
+    }
 }
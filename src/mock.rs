@@ -0,0 +1,191 @@
+//! A scriptable, in-memory implementation of [`NekosBestApi`], to test
+//! code using the API without a network.
+//!
+//! ```rust
+//! # #[tokio::main]
+//! # async fn main() {
+//! use nekosbest::api::NekosBestApi;
+//! use nekosbest::details::{Details, GifDetails};
+//! use nekosbest::mock::{Call, MockApi};
+//! use nekosbest::{Category, NekosBestResponseSingle};
+//!
+//! let api = MockApi::new();
+//! api.push_get(Ok(NekosBestResponseSingle {
+//!     url: "https://nekos.best/api/v2/hug/1.gif".to_owned(),
+//!     details: Details::Gif(GifDetails {
+//!         anime_name: "Senko-san".to_owned(),
+//!     }),
+//! }));
+//!
+//! let resp = api.get(Category::Hug.into()).await.unwrap();
+//! assert_eq!(resp.url, "https://nekos.best/api/v2/hug/1.gif");
+//! assert_eq!(api.calls(), vec![Call::Get(Category::Hug.into())]);
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+#[cfg(feature = "blocking")]
+use nb_blocking_util::blocking;
+
+use crate::api::NekosBestApi;
+use crate::details::Details;
+use crate::{AnyCategory, NekosBestError, NekosBestResponse, NekosBestResponseSingle, SearchQuery};
+
+/// A call made to a [`MockApi`], with its arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Call {
+    Get(AnyCategory),
+    GetAmount(AnyCategory, u8),
+    Search(SearchQuery),
+    Details(String),
+    #[cfg(feature = "metrics")]
+    Metrics,
+    /// A download, with the url of the downloaded response.
+    #[cfg(feature = "download")]
+    Download(String),
+}
+
+type Script<T> = VecDeque<Result<T, NekosBestError>>;
+
+#[derive(Default)]
+struct Inner {
+    calls: Vec<Call>,
+    get: Script<NekosBestResponseSingle>,
+    get_amount: Script<NekosBestResponse>,
+    search: Script<NekosBestResponse>,
+    details: Script<Details>,
+    #[cfg(feature = "metrics")]
+    metrics: Script<crate::metrics::Metrics>,
+    #[cfg(feature = "download")]
+    download: Script<crate::download::DownloadResult>,
+}
+
+/// A fake [`NekosBestApi`], which returns the results queued with the
+/// `push_*` methods in order, and records all the calls made to it.
+///
+/// Calls with no queued result fail with [`NekosBestError::NotFound`].
+#[derive(Default)]
+pub struct MockApi {
+    inner: Mutex<Inner>,
+}
+
+impl MockApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn inner(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn next<T>(
+        &self,
+        call: Call,
+        script: impl FnOnce(&mut Inner) -> &mut Script<T>,
+    ) -> Result<T, NekosBestError> {
+        let mut inner = self.inner();
+        inner.calls.push(call);
+        script(&mut inner)
+            .pop_front()
+            .unwrap_or(Err(NekosBestError::NotFound))
+    }
+
+    /// Queues the result of a call to [`NekosBestApi::get`].
+    pub fn push_get(&self, result: Result<NekosBestResponseSingle, NekosBestError>) -> &Self {
+        self.inner().get.push_back(result);
+        self
+    }
+
+    /// Queues the result of a call to [`NekosBestApi::get_amount`].
+    pub fn push_get_amount(&self, result: Result<NekosBestResponse, NekosBestError>) -> &Self {
+        self.inner().get_amount.push_back(result);
+        self
+    }
+
+    /// Queues the result of a call to [`NekosBestApi::search`].
+    pub fn push_search(&self, result: Result<NekosBestResponse, NekosBestError>) -> &Self {
+        self.inner().search.push_back(result);
+        self
+    }
+
+    /// Queues the result of a call to [`NekosBestApi::details`].
+    pub fn push_details(&self, result: Result<Details, NekosBestError>) -> &Self {
+        self.inner().details.push_back(result);
+        self
+    }
+
+    /// Queues the result of a call to [`NekosBestApi::metrics`].
+    #[cfg(feature = "metrics")]
+    pub fn push_metrics(&self, result: Result<crate::metrics::Metrics, NekosBestError>) -> &Self {
+        self.inner().metrics.push_back(result);
+        self
+    }
+
+    /// Queues the result of a call to [`NekosBestApi::download`].
+    #[cfg(feature = "download")]
+    pub fn push_download(
+        &self,
+        result: Result<crate::download::DownloadResult, NekosBestError>,
+    ) -> &Self {
+        self.inner().download.push_back(result);
+        self
+    }
+
+    /// The calls made so far, in order.
+    pub fn calls(&self) -> Vec<Call> {
+        self.inner().calls.clone()
+    }
+
+    /// Forgets the calls made so far.
+    pub fn clear_calls(&self) {
+        self.inner().calls.clear();
+    }
+}
+
+#[cfg_attr(not(feature = "blocking"), async_trait::async_trait)]
+impl NekosBestApi for MockApi {
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn get(&self, category: AnyCategory) -> Result<NekosBestResponseSingle, NekosBestError> {
+        self.next(Call::Get(category), |inner| &mut inner.get)
+    }
+
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn get_amount(
+        &self,
+        category: AnyCategory,
+        amount: u8,
+    ) -> Result<NekosBestResponse, NekosBestError> {
+        self.next(Call::GetAmount(category, amount), |inner| {
+            &mut inner.get_amount
+        })
+    }
+
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn search(&self, query: SearchQuery) -> Result<NekosBestResponse, NekosBestError> {
+        self.next(Call::Search(query), |inner| &mut inner.search)
+    }
+
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn details(&self, url: &str) -> Result<Details, NekosBestError> {
+        self.next(Call::Details(url.to_owned()), |inner| &mut inner.details)
+    }
+
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn metrics(&self) -> Result<crate::metrics::Metrics, NekosBestError> {
+        self.next(Call::Metrics, |inner| &mut inner.metrics)
+    }
+
+    #[cfg(feature = "download")]
+    #[cfg_attr(feature = "blocking", blocking)]
+    async fn download(
+        &self,
+        response: &NekosBestResponseSingle,
+    ) -> Result<crate::download::DownloadResult, NekosBestError> {
+        self.next(Call::Download(response.url.clone()), |inner| {
+            &mut inner.download
+        })
+    }
+}
@@ -0,0 +1,198 @@
+//! Human-readable names, descriptions and emoji of the categories,
+//! for slash-command choices, help pages or autocompletion.
+//!
+//! ```rust
+//! # use nekosbest::Category;
+//! # use nekosbest::names::CategoryNames;
+//! assert_eq!(Category::ThumbsUp.display_name(), "Thumbs up");
+//! assert_eq!(Category::ThumbsUp.emoji(), "👍");
+//!
+//! let mut names = CategoryNames::new();
+//! names.set("fr", Category::ThumbsUp, "Pouce levé", "Lever le pouce");
+//! assert_eq!(names.display_name("fr", Category::ThumbsUp), "Pouce levé");
+//! // falls back to English
+//! assert_eq!(names.description("fr", Category::Hug), "Hug someone");
+//! ```
+
+use std::collections::HashMap;
+
+use crate::interaction::DEFAULT_LOCALE;
+use crate::Category;
+
+macro_rules! category_names {
+    ($($cat_name:ident => $name:literal, $description:literal, $emoji:literal;)*) => {
+        impl Category {
+            /// The English display name of this category, see [`CategoryNames`]
+            /// for the other locales.
+            pub const fn display_name(self) -> &'static str {
+                match self {
+                    $(Category::$cat_name => $name,)*
+                }
+            }
+
+            /// A short English description of this category, see [`CategoryNames`]
+            /// for the other locales.
+            pub const fn description(self) -> &'static str {
+                match self {
+                    $(Category::$cat_name => $description,)*
+                }
+            }
+
+            /// An emoji representing this category.
+            pub const fn emoji(self) -> &'static str {
+                match self {
+                    $(Category::$cat_name => $emoji,)*
+                }
+            }
+        }
+    };
+}
+
+category_names! {
+    Angry => "Angry", "Show that you're angry", "😠";
+    Baka => "Baka", "Call someone a baka", "😤";
+    Bite => "Bite", "Bite someone", "😬";
+    Blush => "Blush", "Blush", "😳";
+    Bored => "Bored", "Show that you're bored", "😑";
+    Cry => "Cry", "Cry", "😢";
+    Cuddle => "Cuddle", "Cuddle someone", "🫂";
+    Dance => "Dance", "Dance", "💃";
+    Facepalm => "Facepalm", "Facepalm", "🤦";
+    Feed => "Feed", "Feed someone", "🍙";
+    Handhold => "Hold hands", "Hold hands with someone", "🫶";
+    Handshake => "Handshake", "Shake hands with someone", "🤝";
+    Happy => "Happy", "Show that you're happy", "😄";
+    Highfive => "High five", "High-five someone", "🙌";
+    Hug => "Hug", "Hug someone", "🤗";
+    Husbando => "Husbando", "A random husbando picture", "🤵";
+    Kick => "Kick", "Kick someone", "🦵";
+    Kiss => "Kiss", "Kiss someone", "💋";
+    Kitsune => "Kitsune", "A random kitsune picture", "🦊";
+    Laugh => "Laugh", "Laugh", "😂";
+    Lurk => "Lurk", "Lurk in the shadows", "🫣";
+    Neko => "Neko", "A random neko picture", "🐱";
+    Nod => "Nod", "Nod", "😌";
+    Nom => "Nom", "Eat something", "😋";
+    Nope => "Nope", "Say nope", "🙅";
+    Pat => "Pat", "Give someone headpats", "🫳";
+    Peck => "Peck", "Give someone a peck", "😘";
+    Poke => "Poke", "Poke someone", "👉";
+    Pout => "Pout", "Pout", "🙁";
+    Punch => "Punch", "Punch someone", "👊";
+    Run => "Run", "Run away", "🏃";
+    Shoot => "Shoot", "Shoot someone", "🔫";
+    Shrug => "Shrug", "Shrug", "🤷";
+    Slap => "Slap", "Slap someone", "🖐️";
+    Sleep => "Sleep", "Fall asleep", "😴";
+    Smile => "Smile", "Smile", "😊";
+    Smug => "Smug", "Look smug", "😏";
+    Stare => "Stare", "Stare at someone", "👀";
+    Think => "Think", "Think about something", "🤔";
+    ThumbsUp => "Thumbs up", "Give a thumbs up", "👍";
+    Tickle => "Tickle", "Tickle someone", "🤭";
+    Waifu => "Waifu", "A random waifu picture", "🎀";
+    Wave => "Wave", "Wave at someone", "👋";
+    Wink => "Wink", "Wink at someone", "😉";
+    Yawn => "Yawn", "Yawn", "🥱";
+    Yeet => "Yeet", "Yeet someone", "🚀";
+}
+
+/// The name and description of a category in some locale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalizedCategory {
+    pub name: String,
+    pub description: String,
+}
+
+/// Names and descriptions of the categories per locale, falling back
+/// to the built-in English ones ([`Category::display_name`] and
+/// [`Category::description`]).
+#[derive(Debug, Clone, Default)]
+pub struct CategoryNames {
+    locales: HashMap<String, HashMap<Category, LocalizedCategory>>,
+}
+
+impl CategoryNames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name and description of `category` for `locale`.
+    pub fn set(
+        &mut self,
+        locale: impl Into<String>,
+        category: Category,
+        name: impl Into<String>,
+        description: impl Into<String>,
+    ) -> &mut Self {
+        self.locales.entry(locale.into()).or_default().insert(
+            category,
+            LocalizedCategory {
+                name: name.into(),
+                description: description.into(),
+            },
+        );
+        self
+    }
+
+    /// Adds a whole table for `locale`, like one loaded from a file.
+    pub fn extend(
+        &mut self,
+        locale: impl Into<String>,
+        table: impl IntoIterator<Item = (Category, LocalizedCategory)>,
+    ) -> &mut Self {
+        self.locales.entry(locale.into()).or_default().extend(table);
+        self
+    }
+
+    fn get(&self, locale: &str, category: Category) -> Option<&LocalizedCategory> {
+        [locale, DEFAULT_LOCALE]
+            .iter()
+            .find_map(|locale| self.locales.get(*locale)?.get(&category))
+    }
+
+    /// The name of `category` in `locale`.
+    pub fn display_name(&self, locale: &str, category: Category) -> &str {
+        self.get(locale, category)
+            .map_or(category.display_name(), |l| &l.name)
+    }
+
+    /// The description of `category` in `locale`.
+    pub fn description(&self, locale: &str, category: Category) -> &str {
+        self.get(locale, category)
+            .map_or(category.description(), |l| &l.description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn category_names() {
+        use std::collections::HashSet;
+
+        let emoji = Category::ALL_VARIANTS
+            .iter()
+            .map(|category| category.emoji())
+            .collect::<HashSet<_>>();
+        assert_eq!(emoji.len(), Category::ALL_VARIANTS.len());
+        assert_eq!(Category::Highfive.display_name(), "High five");
+
+        let mut names = CategoryNames::new();
+        names.extend(
+            "de",
+            [(
+                Category::Hug,
+                LocalizedCategory {
+                    name: "Umarmung".to_owned(),
+                    description: "Jemanden umarmen".to_owned(),
+                },
+            )],
+        );
+        assert_eq!(names.display_name("de", Category::Hug), "Umarmung");
+        assert_eq!(names.description("de", Category::Hug), "Jemanden umarmen");
+        assert_eq!(names.display_name("de", Category::Pat), "Pat");
+        assert_eq!(names.display_name("es", Category::Hug), "Hug");
+    }
+}
@@ -0,0 +1,284 @@
+//! A pool of images fetched ahead of time, so that answering a command
+//! usually doesn't wait on the API at all.
+//!
+//! A background task keeps a buffer per category: once a buffer drops to
+//! the low watermark, it is refilled up to the high watermark.
+//!
+//! ```rust ,no_run
+//! # use nekosbest::{Category, CategorySet};
+//! # use nekosbest::prefetch::PrefetchPool;
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), nekosbest::NekosBestError> {
+//! let client = nekosbest::client::Client::new(Default::default());
+//! let pool = PrefetchPool::builder(&client)
+//!     .categories(CategorySet::from_slice(&[Category::Hug, Category::Pat]))
+//!     .watermarks(5, 20)
+//!     .spawn();
+//!
+//! // usually served from the buffer, falls back to a request otherwise
+//! let hug = pool.take(Category::Hug).await?;
+//! println!("{}", hug.url);
+//!
+//! pool.shutdown().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use crate::client::Client;
+use crate::{Amount, Category, CategorySet, NekosBestError, NekosBestResponseSingle};
+
+/// A builder for a [`PrefetchPool`].
+#[must_use]
+pub struct PrefetchPoolBuilder {
+    client: Client,
+    categories: CategorySet,
+    low_watermark: usize,
+    high_watermark: usize,
+    retry_delay: Duration,
+}
+
+impl PrefetchPoolBuilder {
+    /// The categories to keep buffered, all of them by default.
+    pub fn categories(mut self, categories: CategorySet) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    /// A buffer is refilled up to `high` images once it holds `low`
+    /// images or less. 5 and 20 by default.
+    ///
+    /// # Panics
+    /// If `low` is not below `high`.
+    pub fn watermarks(mut self, low: usize, high: usize) -> Self {
+        assert!(low < high, "the low watermark must be below the high watermark");
+        self.low_watermark = low;
+        self.high_watermark = high;
+        self
+    }
+
+    /// How long to wait before refilling again after a failed request
+    /// or an empty page, 5 seconds by default.
+    pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Starts the background task filling the buffers.
+    ///
+    /// # Panics
+    /// If called outside of a tokio runtime.
+    pub fn spawn(self) -> PrefetchPool {
+        let shared = Arc::new(Shared {
+            client: self.client,
+            categories: self.categories,
+            low_watermark: self.low_watermark,
+            high_watermark: self.high_watermark,
+            retry_delay: self.retry_delay,
+            buffers: Mutex::new(HashMap::new()),
+            low: Notify::new(),
+            stop: Notify::new(),
+            shutdown: AtomicBool::new(false),
+        });
+        let task = tokio::spawn(refill(Arc::clone(&shared)));
+
+        PrefetchPool {
+            shared,
+            task: Some(task),
+        }
+    }
+}
+
+struct Shared {
+    client: Client,
+    categories: CategorySet,
+    low_watermark: usize,
+    high_watermark: usize,
+    retry_delay: Duration,
+    buffers: Mutex<Buffers>,
+    /// Notified when a buffer drops to the low watermark.
+    low: Notify,
+    /// Notified on shutdown, interrupting whatever the task is waiting on.
+    stop: Notify,
+    shutdown: AtomicBool,
+}
+
+type Buffers = HashMap<Category, VecDeque<NekosBestResponseSingle>>;
+
+impl Shared {
+    fn buffers(&self) -> MutexGuard<'_, Buffers> {
+        self.buffers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn buffered(&self, category: Category) -> usize {
+        self.buffers().get(&category).map_or(0, VecDeque::len)
+    }
+
+    fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::Acquire)
+    }
+
+    fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::Release);
+        self.stop.notify_one();
+    }
+}
+
+/// The background task, refilling the buffers until shutdown, even while
+/// a request is waiting on the rate limit or a retry.
+async fn refill(shared: Arc<Shared>) {
+    tokio::select! {
+        () = refill_buffers(&shared) => {}
+        () = shared.stop.notified() => {}
+    }
+}
+
+/// Refills the buffers below the low watermark.
+async fn refill_buffers(shared: &Shared) {
+    while !shared.is_shutdown() {
+        let mut failed = false;
+
+        for category in shared.categories.iter() {
+            if shared.buffered(category) > shared.low_watermark {
+                continue;
+            }
+
+            loop {
+                if shared.is_shutdown() {
+                    return;
+                }
+
+                let missing = shared
+                    .high_watermark
+                    .saturating_sub(shared.buffered(category));
+                if missing == 0 {
+                    break;
+                }
+
+                let amount = missing.min(usize::from(Amount::MAX)) as u8;
+                let resp = crate::get_with_client_amount(&shared.client, category, amount).await;
+                let grew = match resp {
+                    Ok(resp) => {
+                        let mut buffers = shared.buffers();
+                        let buffer = buffers.entry(category).or_default();
+                        let before = buffer.len();
+                        buffer.extend(resp.0);
+                        buffer.truncate(shared.high_watermark);
+                        buffer.len() > before
+                    }
+                    Err(_) => false,
+                };
+                // an error or an empty page, try again later
+                if !grew {
+                    failed = true;
+                    break;
+                }
+            }
+        }
+
+        // taking images doesn't cut the retry delay short
+        if failed {
+            tokio::time::sleep(shared.retry_delay).await;
+        } else {
+            shared.low.notified().await;
+        }
+    }
+}
+
+/// A pool of images fetched ahead of time, see the [module docs](self).
+///
+/// Dropping the pool stops the background task.
+pub struct PrefetchPool {
+    shared: Arc<Shared>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl PrefetchPool {
+    pub fn builder(client: &Client) -> PrefetchPoolBuilder {
+        PrefetchPoolBuilder {
+            client: client.clone(),
+            categories: CategorySet::ALL,
+            low_watermark: 5,
+            high_watermark: 20,
+            retry_delay: Duration::from_secs(5),
+        }
+    }
+
+    /// Takes a buffered image, if there is one.
+    pub fn try_take(&self, category: Category) -> Option<NekosBestResponseSingle> {
+        let (image, left) = {
+            let mut buffers = self.shared.buffers();
+            let buffer = buffers.get_mut(&category)?;
+            (buffer.pop_front()?, buffer.len())
+        };
+
+        if left <= self.shared.low_watermark {
+            self.shared.low.notify_one();
+        }
+
+        Some(image)
+    }
+
+    /// Takes a buffered image, or fetches one if the buffer is empty
+    /// or `category` is not part of the pool.
+    ///
+    /// # Errors
+    /// Any errors that can happen when fetching, refer to [`NekosBestError`].
+    pub async fn take(&self, category: Category) -> Result<NekosBestResponseSingle, NekosBestError> {
+        match self.try_take(category) {
+            Some(image) => Ok(image),
+            None => crate::get_with_client(&self.shared.client, category).await,
+        }
+    }
+
+    /// The number of buffered images of `category`.
+    pub fn buffered(&self, category: Category) -> usize {
+        self.shared.buffered(category)
+    }
+
+    /// A handle that can stop the background task from anywhere.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle(Arc::clone(&self.shared))
+    }
+
+    /// Stops the background task, and waits for it to finish.
+    pub async fn shutdown(mut self) {
+        self.shared.shutdown();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for PrefetchPool {
+    fn drop(&mut self) {
+        self.shared.shutdown();
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// Stops the background task of a [`PrefetchPool`].
+///
+/// The images already buffered can still be taken afterwards,
+/// and [`PrefetchPool::take`] keeps fetching the missing ones.
+#[derive(Clone)]
+pub struct ShutdownHandle(Arc<Shared>);
+
+impl ShutdownHandle {
+    pub fn shutdown(&self) {
+        self.0.shutdown();
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.0.is_shutdown()
+    }
+}
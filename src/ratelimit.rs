@@ -0,0 +1,511 @@
+use std::ops::Add;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+#[cfg(feature = "blocking")]
+use std::{sync::Condvar, time::Instant};
+#[cfg(not(feature = "blocking"))]
+use tokio::{sync::Notify, time::Instant};
+
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+
+use crate::client::{RatelimitBehavior, RatelimitBucket};
+use crate::NekosBestError;
+
+/// How long to wait for the response to a probe, before assuming
+/// it got lost and sending another one.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+enum Window {
+    /// Nothing is known about the current window, either because no request
+    /// was made yet, or because the last known window expired.
+    ///
+    /// Only a single request (the "probe") is let through, and the others
+    /// wait for its response to learn about the new window.
+    Unknown,
+    /// The server doesn't report rate limits for this bucket.
+    Untracked,
+    Known {
+        /// The requests that can still be sent in this window, minus
+        /// the ones that were already reserved locally.
+        remaining: u32,
+        resets_at: Instant,
+        /// The reset time, as reported by the server, used to
+        /// tell windows apart.
+        reset: DateTime<Utc>,
+    },
+}
+
+struct State {
+    window: Window,
+    probing: bool,
+    in_flight: u32,
+}
+
+enum Reservation {
+    Granted { probe: bool },
+    WaitUntil(Instant),
+    WaitForProbe,
+}
+
+impl State {
+    fn reserve(&mut self, now: Instant) -> Reservation {
+        if let Window::Known { resets_at, .. } = self.window {
+            if now >= resets_at {
+                self.window = Window::Unknown;
+            }
+        }
+
+        let probe = match &mut self.window {
+            Window::Known {
+                remaining: 0,
+                resets_at,
+                ..
+            } => return Reservation::WaitUntil(*resets_at),
+            Window::Known { remaining, .. } => {
+                *remaining -= 1;
+                false
+            }
+            Window::Untracked => false,
+            Window::Unknown if self.probing => return Reservation::WaitForProbe,
+            Window::Unknown => {
+                self.probing = true;
+                true
+            }
+        };
+
+        self.in_flight += 1;
+        Reservation::Granted { probe }
+    }
+
+    fn update(&mut self, probe: bool, status: StatusCode, headers: &HeaderMap) {
+        match (remaining(headers), reset(headers)) {
+            (Some(remaining), Some(reset)) => {
+                let Ok(reset_in) = (reset - Utc::now()).to_std() else {
+                    self.window = Window::Unknown;
+                    return;
+                };
+
+                // the other requests in flight may not have been
+                // counted by the server yet
+                let remaining = remaining.saturating_sub(self.in_flight - 1);
+
+                let remaining = match self.window {
+                    // responses can arrive out of order, so within the same
+                    // window, never hand out more than what was left
+                    Window::Known {
+                        remaining: local,
+                        reset: known_reset,
+                        ..
+                    } if known_reset == reset => remaining.min(local),
+                    _ => remaining,
+                };
+
+                self.window = Window::Known {
+                    remaining,
+                    resets_at: Instant::now().add(reset_in),
+                    reset,
+                };
+            }
+            _ if status == StatusCode::TOO_MANY_REQUESTS => {
+                // No `X-Rate-Limit-*` headers, but we were still rate limited,
+                // so hold off until the server tells us to.
+                let retry_after =
+                    crate::client::retry_after(headers).unwrap_or(Duration::from_secs(1));
+                self.window = Window::Known {
+                    remaining: 0,
+                    resets_at: Instant::now().add(retry_after),
+                    reset: Utc::now().add(retry_after),
+                };
+            }
+            _ => {
+                if probe && matches!(self.window, Window::Unknown) {
+                    self.window = Window::Untracked;
+                }
+            }
+        }
+    }
+}
+
+fn remaining(headers: &HeaderMap) -> Option<u32> {
+    headers
+        .get("X-Rate-Limit-Remaining")?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+fn reset(headers: &HeaderMap) -> Option<DateTime<Utc>> {
+    headers
+        .get("X-Rate-Limit-Reset")?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// A FIFO queue of the threads waiting for a permit.
+#[cfg(feature = "blocking")]
+#[derive(Default)]
+struct Queue {
+    /// The next ticket to hand out, and the ticket being served.
+    tickets: Mutex<(u64, u64)>,
+    turn: Condvar,
+}
+
+#[cfg(feature = "blocking")]
+impl Queue {
+    fn enter(&self) -> QueueTurn<'_> {
+        let mut tickets = self.tickets.lock().unwrap_or_else(PoisonError::into_inner);
+        let ticket = tickets.0;
+        tickets.0 += 1;
+        while tickets.1 != ticket {
+            tickets = self
+                .turn
+                .wait(tickets)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        QueueTurn(self)
+    }
+}
+
+#[cfg(feature = "blocking")]
+struct QueueTurn<'a>(&'a Queue);
+
+#[cfg(feature = "blocking")]
+impl Drop for QueueTurn<'_> {
+    fn drop(&mut self) {
+        self.0
+            .tickets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .1 += 1;
+        self.0.turn.notify_all();
+    }
+}
+
+/// The rate limit state of a single [`RatelimitBucket`].
+///
+/// Permits are reserved locally before sending a request, by decrementing
+/// the remaining requests reported by the server, so concurrent requests
+/// cannot exceed the limit. Waiters are served in FIFO order, and once a
+/// window resets, a single request is let through first to learn about
+/// the new window, instead of releasing all the waiters at once.
+pub(crate) struct Ratelimiter {
+    state: Mutex<State>,
+    #[cfg(not(feature = "blocking"))]
+    queue: tokio::sync::Mutex<()>,
+    #[cfg(not(feature = "blocking"))]
+    probe_done: Notify,
+    #[cfg(feature = "blocking")]
+    queue: Queue,
+    #[cfg(feature = "blocking")]
+    probe_done: Condvar,
+}
+
+impl Default for Ratelimiter {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(State {
+                window: Window::Unknown,
+                probing: false,
+                in_flight: 0,
+            }),
+            queue: Default::default(),
+            probe_done: Default::default(),
+        }
+    }
+}
+
+impl Ratelimiter {
+    fn state(&self) -> MutexGuard<'_, State> {
+        // the state is always left consistent, so poisoning can be ignored
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Reserves a permit to send a request, waiting for it
+    /// (or erroring) according to `behavior`.
+    #[cfg(not(feature = "blocking"))]
+    pub(crate) async fn acquire(
+        &self,
+        behavior: RatelimitBehavior,
+    ) -> Result<Permit<'_>, NekosBestError> {
+        let _turn = self.queue.lock().await;
+
+        loop {
+            // created before looking at the state, so a probe
+            // finishing in between is not missed
+            let probe_done = self.probe_done.notified();
+            let reservation = self.state().reserve(Instant::now());
+
+            match reservation {
+                Reservation::Granted { probe } => return Ok(Permit::new(self, probe)),
+                Reservation::WaitUntil(_) if matches!(behavior, RatelimitBehavior::Error) => {
+                    return Err(NekosBestError::RateLimited);
+                }
+                Reservation::WaitUntil(resets_at) => tokio::time::sleep_until(resets_at).await,
+                Reservation::WaitForProbe => {
+                    if tokio::time::timeout(PROBE_TIMEOUT, probe_done).await.is_err() {
+                        self.state().probing = false;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reserves a permit to send a request, waiting for it
+    /// (or erroring) according to `behavior`.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn acquire(&self, behavior: RatelimitBehavior) -> Result<Permit<'_>, NekosBestError> {
+        let _turn = self.queue.enter();
+        let mut state = self.state();
+
+        loop {
+            match state.reserve(Instant::now()) {
+                Reservation::Granted { probe } => {
+                    drop(state);
+                    return Ok(Permit::new(self, probe));
+                }
+                Reservation::WaitUntil(_) if matches!(behavior, RatelimitBehavior::Error) => {
+                    return Err(NekosBestError::RateLimited);
+                }
+                Reservation::WaitUntil(resets_at) => {
+                    drop(state);
+                    std::thread::sleep(resets_at.saturating_duration_since(Instant::now()));
+                    state = self.state();
+                }
+                Reservation::WaitForProbe => {
+                    let (s, timeout) = self
+                        .probe_done
+                        .wait_timeout(state, PROBE_TIMEOUT)
+                        .unwrap_or_else(PoisonError::into_inner);
+                    state = s;
+                    if timeout.timed_out() {
+                        state.probing = false;
+                    }
+                }
+            }
+        }
+    }
+
+    fn probe_finished(&self) {
+        #[cfg(not(feature = "blocking"))]
+        self.probe_done.notify_waiters();
+        #[cfg(feature = "blocking")]
+        self.probe_done.notify_all();
+    }
+}
+
+/// A reservation to send a single request, released when dropped.
+pub(crate) struct Permit<'a> {
+    limiter: &'a Ratelimiter,
+    probe: bool,
+}
+
+impl<'a> Permit<'a> {
+    fn new(limiter: &'a Ratelimiter, probe: bool) -> Self {
+        Self { limiter, probe }
+    }
+
+    /// Updates the rate limit state from the response to
+    /// the request this permit was reserved for.
+    pub(crate) fn update(&self, status: StatusCode, headers: &HeaderMap) {
+        self.limiter.state().update(self.probe, status, headers);
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.limiter.state();
+        state.in_flight -= 1;
+        if self.probe && state.probing {
+            state.probing = false;
+            drop(state);
+            self.limiter.probe_finished();
+        }
+    }
+}
+
+/// The rate limit state of every [`RatelimitBucket`].
+#[derive(Default)]
+pub(crate) struct Ratelimits {
+    category: Ratelimiter,
+    search: Ratelimiter,
+    metrics: Ratelimiter,
+    assets: Ratelimiter,
+}
+
+impl Ratelimits {
+    pub(crate) fn bucket(&self, bucket: RatelimitBucket) -> &Ratelimiter {
+        match bucket {
+            RatelimitBucket::Category => &self.category,
+            RatelimitBucket::Search => &self.search,
+            RatelimitBucket::Metrics => &self.metrics,
+            RatelimitBucket::Assets => &self.assets,
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "blocking")))]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+
+    fn headers(remaining: u32, reset_in: Duration) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Rate-Limit-Remaining", remaining.into());
+        let reset = (Utc::now() + reset_in).to_rfc3339();
+        headers.insert("X-Rate-Limit-Reset", reset.parse().unwrap());
+        headers
+    }
+
+    async fn learn_window(limiter: &Ratelimiter, remaining: u32) {
+        let probe = limiter.acquire(RatelimitBehavior::Error).await.unwrap();
+        probe.update(StatusCode::OK, &headers(remaining, Duration::from_secs(60)));
+    }
+
+    #[tokio::test]
+    async fn waits_for_probe() {
+        let limiter = Arc::new(Ratelimiter::default());
+
+        let probe = limiter.acquire(RatelimitBehavior::Error).await.unwrap();
+        assert!(probe.probe);
+
+        let waiter = tokio::spawn({
+            let limiter = Arc::clone(&limiter);
+            async move { limiter.acquire(RatelimitBehavior::Error).await.map(drop) }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        probe.update(StatusCode::OK, &headers(1, Duration::from_secs(60)));
+        drop(probe);
+        waiter.await.unwrap().unwrap();
+
+        // the only remaining request was reserved by the waiter
+        assert!(matches!(
+            limiter.acquire(RatelimitBehavior::Error).await,
+            Err(NekosBestError::RateLimited)
+        ));
+    }
+
+    #[tokio::test]
+    async fn reserves_before_sending() {
+        let limiter = Ratelimiter::default();
+        learn_window(&limiter, 3).await;
+
+        let permits = [
+            limiter.acquire(RatelimitBehavior::Error).await.unwrap(),
+            limiter.acquire(RatelimitBehavior::Error).await.unwrap(),
+            limiter.acquire(RatelimitBehavior::Error).await.unwrap(),
+        ];
+        assert!(limiter.acquire(RatelimitBehavior::Error).await.is_err());
+
+        // a response must not hand out the permits still in flight again
+        permits[0].update(StatusCode::OK, &headers(2, Duration::from_secs(60)));
+        assert!(limiter.acquire(RatelimitBehavior::Error).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn untracked_buckets_are_not_limited() {
+        let limiter = Ratelimiter::default();
+        let probe = limiter.acquire(RatelimitBehavior::Error).await.unwrap();
+        probe.update(StatusCode::OK, &HeaderMap::new());
+        drop(probe);
+
+        let _permits = [
+            limiter.acquire(RatelimitBehavior::Error).await.unwrap(),
+            limiter.acquire(RatelimitBehavior::Error).await.unwrap(),
+        ];
+    }
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod blocking_test {
+    use super::*;
+    use std::sync::Arc;
+
+    fn headers(remaining: u32, reset_in: Duration) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Rate-Limit-Remaining", remaining.into());
+        let reset = (Utc::now() + reset_in).to_rfc3339();
+        headers.insert("X-Rate-Limit-Reset", reset.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn waits_for_probe() {
+        let limiter = Arc::new(Ratelimiter::default());
+
+        let probe = limiter.acquire(RatelimitBehavior::Error).unwrap();
+        assert!(probe.probe);
+
+        let waiter = std::thread::spawn({
+            let limiter = Arc::clone(&limiter);
+            move || limiter.acquire(RatelimitBehavior::Error).map(drop)
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+
+        probe.update(StatusCode::OK, &headers(1, Duration::from_secs(60)));
+        drop(probe);
+        waiter.join().unwrap().unwrap();
+
+        // the only remaining request was reserved by the waiter
+        assert!(matches!(
+            limiter.acquire(RatelimitBehavior::Error),
+            Err(NekosBestError::RateLimited)
+        ));
+    }
+
+    #[test]
+    fn reserves_before_sending() {
+        let limiter = Ratelimiter::default();
+        let probe = limiter.acquire(RatelimitBehavior::Error).unwrap();
+        probe.update(StatusCode::OK, &headers(3, Duration::from_secs(60)));
+        drop(probe);
+
+        let permits = [
+            limiter.acquire(RatelimitBehavior::Error).unwrap(),
+            limiter.acquire(RatelimitBehavior::Error).unwrap(),
+            limiter.acquire(RatelimitBehavior::Error).unwrap(),
+        ];
+        assert!(limiter.acquire(RatelimitBehavior::Error).is_err());
+
+        // a response must not hand out the permits still in flight again
+        permits[0].update(StatusCode::OK, &headers(2, Duration::from_secs(60)));
+        assert!(limiter.acquire(RatelimitBehavior::Error).is_err());
+    }
+
+    #[test]
+    fn waits_for_reset_in_order() {
+        let limiter = Arc::new(Ratelimiter::default());
+        let probe = limiter.acquire(RatelimitBehavior::Error).unwrap();
+        probe.update(StatusCode::OK, &headers(0, Duration::from_millis(300)));
+        drop(probe);
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let waiters = (0..3)
+            .map(|i| {
+                let limiter = Arc::clone(&limiter);
+                let order = Arc::clone(&order);
+                let waiter = std::thread::spawn(move || {
+                    let _permit = limiter.acquire(RatelimitBehavior::Sleep).unwrap();
+                    order.lock().unwrap().push(i);
+                });
+                // let the waiter take its place in the queue
+                std::thread::sleep(Duration::from_millis(20));
+                waiter
+            })
+            .collect::<Vec<_>>();
+
+        for waiter in waiters {
+            waiter.join().unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), [0, 1, 2]);
+    }
+}
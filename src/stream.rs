@@ -0,0 +1,291 @@
+//! Endless streams of random images, for slideshows and feeds.
+//!
+//! The streams fetch pages of [`Amount::MAX`] images behind the scenes,
+//! going through the rate limits of the client, and skip the urls they
+//! already yielded. Nothing is fetched once they are dropped.
+//!
+//! Errors are yielded as they happen. After a transient one (a timeout,
+//! a `5xx`, a `429`, ...) the stream backs off according to the
+//! [`RetryPolicy`] of the client and goes on, but it ends after any other
+//! error, like a `404` for an unknown category, after
+//! [`MAX_CONSECUTIVE_ERRORS`] transient errors in a row, or after
+//! [`MAX_EMPTY_PAGES`] pages in a row without any new url. A stream never
+//! yields the same url twice, so one over a small category ends once most
+//! of its images were yielded.
+//!
+//! ```rust ,no_run
+//! # use futures::StreamExt;
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), nekosbest::NekosBestError> {
+//! let mut pats = nekosbest::stream(nekosbest::Category::Pat).take(50);
+//! while let Some(pat) = pats.next().await {
+//!     println!("{}", pat?.url);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashSet, VecDeque};
+use std::future::Future;
+use std::time::Duration;
+
+use futures::stream::{BoxStream, Stream, StreamExt};
+
+use crate::client::{default_client, Client, RetryPolicy};
+use crate::{Amount, AnyCategory, NekosBestError, NekosBestResponseSingle};
+
+/// The stream ends after this many transient errors in a row.
+pub const MAX_CONSECUTIVE_ERRORS: u32 = 3;
+
+/// The stream ends after this many pages in a row without any new url.
+pub const MAX_EMPTY_PAGES: usize = 3;
+
+struct State<T, F> {
+    fetch: F,
+    buffer: VecDeque<T>,
+    seen: HashSet<String>,
+    /// The number of pages in a row without any new url.
+    empty_pages: usize,
+    /// The number of transient errors in a row.
+    errors: u32,
+    done: bool,
+}
+
+/// Whether the stream can go on after `e`, following the same rules
+/// as the retries of the client.
+fn is_transient(e: &NekosBestError) -> bool {
+    match e {
+        NekosBestError::ReqwestError(e) => match e.status() {
+            Some(status) => RetryPolicy::should_retry_status(status),
+            None => RetryPolicy::should_retry_error(e),
+        },
+        NekosBestError::RateLimited => true,
+        _ => false,
+    }
+}
+
+fn paged<T, F, Fut>(
+    fetch: F,
+    url: fn(&T) -> &str,
+    retry_policy: RetryPolicy,
+) -> impl Stream<Item = Result<T, NekosBestError>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Vec<T>, NekosBestError>>,
+{
+    let state = State {
+        fetch,
+        buffer: VecDeque::new(),
+        seen: HashSet::new(),
+        empty_pages: 0,
+        errors: 0,
+        done: false,
+    };
+
+    futures::stream::unfold(state, move |mut state| async move {
+        loop {
+            if let Some(item) = state.buffer.pop_front() {
+                return Some((Ok(item), state));
+            }
+            if state.done {
+                return None;
+            }
+
+            if state.errors > 0 {
+                sleep(retry_policy.backoff(state.errors)).await;
+            }
+            let page = match (state.fetch)().await {
+                Ok(page) => page,
+                Err(e) => {
+                    state.errors += 1;
+                    state.done = !is_transient(&e) || state.errors >= MAX_CONSECUTIVE_ERRORS;
+                    return Some((Err(e), state));
+                }
+            };
+            state.errors = 0;
+
+            for item in page {
+                if state.seen.insert(url(&item).to_owned()) {
+                    state.buffer.push_back(item);
+                }
+            }
+
+            if state.buffer.is_empty() {
+                state.empty_pages += 1;
+                state.done = state.empty_pages >= MAX_EMPTY_PAGES;
+            } else {
+                state.empty_pages = 0;
+            }
+        }
+    })
+}
+
+async fn sleep(duration: Duration) {
+    if !duration.is_zero() {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// An endless stream of images, with a supplied client.
+///
+/// Errors are yielded as they happen, and the stream goes on after the
+/// transient ones. The same url is never yielded twice, so the stream
+/// ends once the pages stop bringing new ones, see the [module docs](self).
+pub fn stream_with_client(
+    client: &Client,
+    category: impl Into<AnyCategory>,
+) -> BoxStream<'static, Result<NekosBestResponseSingle, NekosBestError>> {
+    let client = client.clone();
+    let retry_policy = client.retry_policy();
+    let category = category.into();
+
+    paged(
+        move || {
+            let client = client.clone();
+            let category = category.clone();
+            async move {
+                crate::get_with_client_amount(&client, category, Amount::MAX)
+                    .await
+                    .map(|resp| resp.0)
+            }
+        },
+        |resp| &resp.url,
+        retry_policy,
+    )
+    .boxed()
+}
+
+/// An endless stream of images, with the default client,
+/// see [`stream_with_client`].
+pub fn stream(
+    category: impl Into<AnyCategory>,
+) -> BoxStream<'static, Result<NekosBestResponseSingle, NekosBestError>> {
+    stream_with_client(default_client(), category)
+}
+
+/// An endless stream of images of `C`, with a supplied client,
+/// see [`stream_with_client`].
+#[cfg(feature = "strong-types")]
+pub fn st_stream_with_client<C>(
+    client: &Client,
+) -> BoxStream<'static, Result<crate::STNekosBestResponseSingle<C>, NekosBestError>>
+where
+    C: crate::STCategory + Send + 'static,
+    C::Details: Send,
+{
+    let client = client.clone();
+    let retry_policy = client.retry_policy();
+
+    paged(
+        move || {
+            let client = client.clone();
+            async move {
+                crate::st_get_with_client_amount::<C>(&client, Amount::MAX)
+                    .await
+                    .map(|resp| resp.0)
+            }
+        },
+        |resp| &resp.url,
+        retry_policy,
+    )
+    .boxed()
+}
+
+/// An endless stream of images of `C`, with the default client,
+/// see [`stream_with_client`].
+#[cfg(feature = "strong-types")]
+pub fn st_stream<C>(
+) -> BoxStream<'static, Result<crate::STNekosBestResponseSingle<C>, NekosBestError>>
+where
+    C: crate::STCategory + Send + 'static,
+    C::Details: Send,
+{
+    st_stream_with_client(default_client())
+}
+
+impl Client {
+    /// An endless stream of images, see [`stream_with_client`].
+    pub fn stream(
+        &self,
+        category: impl Into<AnyCategory>,
+    ) -> BoxStream<'static, Result<NekosBestResponseSingle, NekosBestError>> {
+        stream_with_client(self, category)
+    }
+
+    /// An endless stream of images of `C`, see [`st_stream_with_client`].
+    #[cfg(feature = "strong-types")]
+    pub fn stream_typed<C>(
+        &self,
+    ) -> BoxStream<'static, Result<crate::STNekosBestResponseSingle<C>, NekosBestError>>
+    where
+        C: crate::STCategory + Send + 'static,
+        C::Details: Send,
+    {
+        st_stream_with_client(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Page = Result<Vec<&'static str>, NekosBestError>;
+
+    /// The items of a stream over `pages`, which fails the test if
+    /// it fetches more pages than that.
+    async fn items(pages: Vec<Page>) -> Vec<Result<&'static str, String>> {
+        let mut pages = pages.into_iter();
+        let retry_policy = RetryPolicy {
+            initial_backoff: Duration::ZERO,
+            ..RetryPolicy::default()
+        };
+
+        paged(
+            move || std::future::ready(pages.next().expect("fetched too many pages")),
+            |url| url,
+            retry_policy,
+        )
+        .map(|item| item.map_err(|e| e.to_string()))
+        .collect()
+        .await
+    }
+
+    #[tokio::test]
+    async fn ends_after_empty_pages() {
+        let pages = vec![Ok(vec![]), Ok(vec!["a"]), Ok(vec![]), Ok(vec![]), Ok(vec![])];
+        assert_eq!(items(pages).await, [Ok("a")]);
+    }
+
+    #[tokio::test]
+    async fn ends_after_pages_without_new_urls() {
+        let pages = vec![
+            Ok(vec!["a", "b"]),
+            Ok(vec!["b", "c"]),
+            Ok(vec!["a"]),
+            Ok(vec!["c", "b"]),
+            Ok(vec!["a", "a"]),
+        ];
+        assert_eq!(items(pages).await, [Ok("a"), Ok("b"), Ok("c")]);
+    }
+
+    #[tokio::test]
+    async fn ends_after_errors() {
+        use NekosBestError::{NotFound, RateLimited};
+
+        let rate_limited = || Err("rate limited".to_owned());
+        let pages = vec![
+            Err(RateLimited),
+            Ok(vec!["a"]),
+            Err(RateLimited),
+            Err(RateLimited),
+            Err(RateLimited),
+        ];
+        assert_eq!(
+            items(pages).await,
+            [rate_limited(), Ok("a"), rate_limited(), rate_limited(), rate_limited()]
+        );
+
+        let pages = vec![Ok(vec!["a", "b"]), Err(NotFound)];
+        assert_eq!(items(pages).await, [Ok("a"), Ok("b"), Err("not found".to_owned())]);
+    }
+}
@@ -1,4 +1,3 @@
-use reqwest::RequestBuilder;
 use std::{
     fmt::Debug,
     ops::{Deref, DerefMut, Index, IndexMut},
@@ -34,49 +33,37 @@ impl<C: STCategory> STNekosBestResponseSingle<C> {
     }
 }
 
-macro_rules! gif_endpoints {
-    ([$($name:ident),* $(,)?]) => {
-        $(
-            gif_endpoints!($name);
-        )*
-    };
-    ($name:ident) => {
-        #[derive(Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-        pub struct $name;
-
-        impl STCategory for $name {
-            const CATEGORY: Category = Category::$name;
-            type Details = GifDetails;
-            type SearchQueryType = STNekosBestSearchQueryGifType;
-        }
-    };
+macro_rules! st_details {
+    (Png) => { ImageDetails };
+    (Gif) => { GifDetails };
 }
 
-gif_endpoints!([
-    Angry, Baka, Bite, Blush, Bored, Cry, Cuddle, Dance, Facepalm, Feed, Handhold, Handshake, Happy,
-    Highfive, Hug, Kick, Kiss, Laugh, Lurk, Nod, Nom, Nope, Pat, Peck, Poke, Pout, Punch, Run, Shoot,
-    Shrug, Slap, Sleep, Smile, Smug, Stare, Think, ThumbsUp, Tickle, Wave, Wink, Yawn, Yeet
-]);
+macro_rules! st_search_query_type {
+    (Png) => { STNekosBestSearchQueryImageType };
+    (Gif) => { STNekosBestSearchQueryGifType };
+}
 
-macro_rules! image_endpoints {
-    ([$($name:ident),* $(,)?]) => {
+macro_rules! strong_categories {
+    ($(
+        $(#[$at:meta])* $(ref $(#[$ref_at:meta])*)?
+        $cat_name:ident => $url_name:literal: $format:ident,
+    )*) => {
         $(
-            image_endpoints!($name);
+            $(#[$ref_at])*
+            #[derive(Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub struct $cat_name;
+
+            $(#[$ref_at])*
+            impl STCategory for $cat_name {
+                const CATEGORY: Category = Category::$cat_name;
+                type Details = st_details!($format);
+                type SearchQueryType = st_search_query_type!($format);
+            }
         )*
     };
-    ($name:ident) => {
-        #[derive(Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-        pub struct $name;
-
-        impl STCategory for $name {
-            const CATEGORY: Category = Category::$name;
-            type Details = ImageDetails;
-            type SearchQueryType = STNekosBestSearchQueryImageType;
-        }
-    };
 }
 
-image_endpoints!([Husbando, Kitsune, Neko, Waifu]);
+crate::category::for_each_category!(strong_categories);
 
 #[deprecated(since = "0.11.0", note = "Use `Neko` instead")]
 pub type Nekos = Neko;
@@ -1,10 +1,13 @@
 #[cfg(feature = "blocking")]
 use nb_blocking_util::blocking;
 
-use crate::client::{Client, ClientConfig};
+use crate::cache::SearchJson;
+use crate::client::{default_client, Client, RatelimitBucket};
 use crate::{
-    strong_types::{STCategory, STNekosBestResponse, STNekosBestResponseSingle},
-    NekosBestError, STNekosBestSearchQuery, BASE_URL,
+    strong_types::{
+        STCategory, STNekosBestResponse, STNekosBestResponseSingle, STNekosBestSearchQueryType,
+    },
+    NekosBestError, STNekosBestSearchQuery,
 };
 
 /// Gets a single image, with a supplied client.
@@ -15,11 +18,9 @@ use crate::{
 pub async fn get_with_client<C: STCategory>(
     client: &Client,
 ) -> Result<STNekosBestResponseSingle<C>, NekosBestError> {
-    let r = crate::prepare_request(client
-        .client
-        .get(format!("{BASE_URL}/{}", C::CATEGORY)))
-        .send()
-        .await?;
+    let req = client.get_request(client.endpoint_url(C::CATEGORY));
+
+    let r = client.send(RatelimitBucket::Category, req).await?;
 
     let mut resp = r
         .error_for_status()?
@@ -31,21 +32,21 @@ pub async fn get_with_client<C: STCategory>(
 }
 
 /// Gets `amount` images, with a supplied client.
-/// Note that the server clamps the amount to the 1..=20 range
+/// The amount must be in the 1..=20 range, see [`Amount`](crate::Amount).
 ///
 /// # Errors
-/// Any errors that can happen, refer to [`NekosBestError`].
+/// [`NekosBestError::InvalidAmount`] if the amount is out of range, or any
+/// errors that can happen, refer to [`NekosBestError`].
 #[cfg_attr(feature = "blocking", blocking)]
 pub async fn get_with_client_amount<C: STCategory>(
     client: &Client,
     amount: impl Into<u8>,
 ) -> Result<STNekosBestResponse<C>, NekosBestError> {
-    let req = crate::prepare_request(client
-        .client
-        .get(format!("{BASE_URL}/{}", C::CATEGORY)))
-        .query(&[("amount", amount.into())]);
+    let amount = crate::Amount::try_from(amount.into())?;
+    let req = client.get_request(client.endpoint_url(C::CATEGORY))
+        .query(&[("amount", amount.get())]);
 
-    let r = req.send().await?;
+    let r = client.send(RatelimitBucket::Category, req).await?;
 
     let v = r.error_for_status()?.json().await?;
 
@@ -58,7 +59,7 @@ pub async fn get_with_client_amount<C: STCategory>(
 /// Any errors that can happen, refer to [`NekosBestError`].
 #[cfg_attr(feature = "blocking", blocking)]
 pub async fn get<C: STCategory>() -> Result<STNekosBestResponseSingle<C>, NekosBestError> {
-    get_with_client(&Client::new(ClientConfig::default())).await
+    get_with_client(default_client()).await
 }
 
 /// Gets `amount` images, with the default client.
@@ -69,36 +70,87 @@ pub async fn get<C: STCategory>() -> Result<STNekosBestResponseSingle<C>, NekosB
 pub async fn get_amount<C: STCategory>(
     amount: impl Into<u8>,
 ) -> Result<STNekosBestResponse<C>, NekosBestError> {
-    get_with_client_amount(&Client::new(ClientConfig::default()), amount).await
+    get_with_client_amount(default_client(), amount).await
 }
 
+/// Searches, with a supplied client.
+///
+/// # Errors
+/// Any errors that can happen, refer to [`NekosBestError`].
 #[cfg_attr(feature = "blocking", blocking)]
 pub async fn search_with_client<C: STCategory>(
     client: &Client,
     query: STNekosBestSearchQuery<C>,
 ) -> Result<STNekosBestResponse<C>, NekosBestError> {
-    #[cfg(not(feature = "blocking"))]
-    client.handle_search_ratelimit().await?;
-
-    let req = crate::prepare_request(client.client.get(format!("{BASE_URL}/search")));
+    // the category and the type are not part of the serialized query
+    let key = format!(
+        "{}:{}:{}",
+        C::CATEGORY.to_url_name(),
+        C::SearchQueryType::TYPE,
+        serde_json::to_string(&query)?,
+    );
+    if let Some(SearchJson(json)) = client.cached(&key) {
+        return Ok(serde_json::from_value(json)?);
+    }
+
+    let req = client.get_request(client.endpoint_url("search"));
 
     let req = query.apply_to(req);
 
-    let res = req.send().await?;
+    let res = client.send(RatelimitBucket::Search, req).await?;
 
-    #[cfg(not(feature = "blocking"))]
-    client.update_search_ratelimit_data(res.headers()).await;
+    let json = res.error_for_status()?.json::<serde_json::Value>().await?;
+    let resp = serde_json::from_value(json.clone())?;
+    client.cache_insert(&key, SearchJson(json));
 
-    Ok(res.error_for_status()?.json().await?)
+    Ok(resp)
 }
 
-#[deprecated(
-    note = "Use `search_with_client` instead, and provide a client.",
-    since = "0.17.0"
-)]
+/// Searches, with the default client.
+///
+/// # Errors
+/// Any errors that can happen, refer to [`NekosBestError`].
 #[cfg_attr(feature = "blocking", blocking)]
 pub async fn search<C: STCategory>(
     query: STNekosBestSearchQuery<C>,
 ) -> Result<STNekosBestResponse<C>, NekosBestError> {
-    search_with_client(&Client::new(ClientConfig::default()), query).await
+    search_with_client(default_client(), query).await
+}
+
+impl Client {
+    /// Gets a single image, see [`st_get_with_client`](crate::st_get_with_client).
+    ///
+    /// # Errors
+    /// Any errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn get_typed<C: STCategory>(
+        &self,
+    ) -> Result<STNekosBestResponseSingle<C>, NekosBestError> {
+        get_with_client(self).await
+    }
+
+    /// Gets `amount` images, see
+    /// [`st_get_with_client_amount`](crate::st_get_with_client_amount).
+    ///
+    /// # Errors
+    /// Any errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn get_many_typed<C: STCategory>(
+        &self,
+        amount: impl Into<u8>,
+    ) -> Result<STNekosBestResponse<C>, NekosBestError> {
+        get_with_client_amount(self, amount).await
+    }
+
+    /// Searches, see [`st_search_with_client`](crate::st_search_with_client).
+    ///
+    /// # Errors
+    /// Any errors that can happen, refer to [`NekosBestError`].
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub async fn search_typed<C: STCategory>(
+        &self,
+        query: STNekosBestSearchQuery<C>,
+    ) -> Result<STNekosBestResponse<C>, NekosBestError> {
+        search_with_client(self, query).await
+    }
 }
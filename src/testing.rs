@@ -0,0 +1,440 @@
+//! A fake nekos.best server running in-process on localhost, to test
+//! every code path of the crate (and of the code using it) offline.
+//!
+//! The server mimics `/endpoints`, `/{category}`, `/search` and
+//! `/metrics`, and serves fixture PNG / GIF assets with the same
+//! headers as the real CDN. Rate limits and failures can be
+//! simulated on demand.
+//!
+//! ```rust
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), nekosbest::NekosBestError> {
+//! let server = nekosbest::testing::FakeServer::start();
+//! let client = server.client();
+//!
+//! let resp = client.get(nekosbest::Category::Neko).await?;
+//! assert!(resp.url.starts_with(&server.api_root()));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json::{json, Value};
+
+use crate::client::{Client, ClientConfig, RatelimitBucket};
+use crate::category::Format;
+use crate::Category;
+
+/// A 2x2 PNG, served for the image categories.
+pub const FIXTURE_PNG: &[u8] = include_bytes!("testing/image.png");
+/// A 1x1 GIF with two frames, served for the gif categories.
+pub const FIXTURE_GIF: &[u8] = include_bytes!("testing/animation.gif");
+
+pub const FIXTURE_ARTIST_NAME: &str = "Fake Artist";
+pub const FIXTURE_ARTIST_HREF: &str = "https://example.com/artist";
+pub const FIXTURE_SOURCE_URL: &str = "https://example.com/source";
+pub const FIXTURE_ANIME_NAME: &str = "Fake Anime: The Movie";
+
+fn is_image(category: Category) -> bool {
+    category.format() == Format::Png
+}
+
+struct Ratelimit {
+    limit: u32,
+    window: Duration,
+    remaining: u32,
+    resets_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    ratelimits: HashMap<RatelimitBucket, Ratelimit>,
+    failures: Vec<StatusCode>,
+    requests: HashMap<RatelimitBucket, usize>,
+    redirects: HashMap<String, String>,
+}
+
+impl State {
+    fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+        state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A fake nekos.best server, listening on localhost until dropped.
+///
+/// Runs on its own thread, so it can be used from both async
+/// and blocking code.
+pub struct FakeServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<tokio::sync::oneshot::Sender<()>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl FakeServer {
+    /// Starts the server on a random port.
+    ///
+    /// # Panics
+    /// If the server cannot be started.
+    pub fn start() -> Self {
+        let state = Arc::new(Mutex::new(State::default()));
+        let (shutdown, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let (addr_tx, addr_rx) = std::sync::mpsc::channel();
+
+        let thread = std::thread::spawn({
+            let state = Arc::clone(&state);
+            move || {
+                let rt = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .expect("failed to start the runtime of the fake server");
+
+                rt.block_on(async move {
+                    let make_svc = make_service_fn(move |_| {
+                        let state = Arc::clone(&state);
+                        async move {
+                            Ok::<_, Infallible>(service_fn(move |req| {
+                                let state = Arc::clone(&state);
+                                async move { Ok::<_, Infallible>(handle(&state, req)) }
+                            }))
+                        }
+                    });
+
+                    let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+                    addr_tx.send(server.local_addr()).unwrap();
+
+                    // not a graceful shutdown: the connections of requests the
+                    // client gave up on may never be closed, and are dropped
+                    // along with the runtime
+                    futures::future::select(server, shutdown_rx).await;
+                });
+            }
+        });
+
+        let addr = addr_rx
+            .recv()
+            .expect("the fake server failed to start");
+
+        Self {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        }
+    }
+
+    /// The address the server listens on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The root of the API, to be used as [`ClientConfig::api_root`].
+    pub fn api_root(&self) -> String {
+        format!("http://{}/api", self.addr)
+    }
+
+    /// A [`ClientConfig`] pointing to this server.
+    pub fn client_config(&self) -> ClientConfig {
+        ClientConfig {
+            api_root: self.api_root(),
+            ..Default::default()
+        }
+    }
+
+    /// A new [`Client`] pointing to this server.
+    pub fn client(&self) -> Client {
+        Client::new(self.client_config())
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        State::lock(&self.state)
+    }
+
+    /// Rate limits the routes of `bucket` to `limit` requests every `window`,
+    /// sending the `X-Rate-Limit-*` headers, and `429 Too Many Requests`
+    /// once the limit is exceeded.
+    pub fn set_ratelimit(&self, bucket: RatelimitBucket, limit: u32, window: Duration) {
+        self.state().ratelimits.insert(
+            bucket,
+            Ratelimit {
+                limit,
+                window,
+                remaining: limit,
+                resets_at: Utc::now() + window,
+            },
+        );
+    }
+
+    /// Removes the rate limit of `bucket`.
+    pub fn remove_ratelimit(&self, bucket: RatelimitBucket) {
+        self.state().ratelimits.remove(&bucket);
+    }
+
+    /// Makes the next requests, regardless of the route, fail with the
+    /// given statuses, in order.
+    pub fn fail_next(&self, statuses: impl IntoIterator<Item = StatusCode>) {
+        self.state().failures.extend(statuses);
+    }
+
+    /// Redirects the requests to the path `from` (like `/api/v2/neko`) to
+    /// the path `to`, keeping the query, with `307 Temporary Redirect`.
+    pub fn redirect(&self, from: &str, to: &str) {
+        self.state().redirects.insert(from.to_owned(), to.to_owned());
+    }
+
+    /// The number of requests made to the routes of `bucket` so far,
+    /// including the failed and rate limited ones.
+    pub fn requests(&self, bucket: RatelimitBucket) -> usize {
+        self.state().requests.get(&bucket).copied().unwrap_or(0)
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn bucket_of(route: &[&str]) -> RatelimitBucket {
+    match route {
+        ["search"] => RatelimitBucket::Search,
+        ["metrics"] => RatelimitBucket::Metrics,
+        [_, _] => RatelimitBucket::Assets,
+        _ => RatelimitBucket::Category,
+    }
+}
+
+fn handle(state: &Mutex<State>, req: Request<Body>) -> Response<Body> {
+    let path = req.uri().path().to_owned();
+    let query: HashMap<String, String> = req
+        .uri()
+        .query()
+        .map(|q| url::form_urlencoded::parse(q.as_bytes()).into_owned().collect())
+        .unwrap_or_default();
+    let host = req
+        .headers()
+        .get(hyper::header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("localhost")
+        .to_owned();
+
+    if let Some(to) = State::lock(state).redirects.get(&path) {
+        let location = match req.uri().query() {
+            Some(query) => format!("{to}?{query}"),
+            None => to.clone(),
+        };
+        return Response::builder()
+            .status(StatusCode::TEMPORARY_REDIRECT)
+            .header(hyper::header::LOCATION, location)
+            .body(Body::empty())
+            .unwrap();
+    }
+
+    // `/api/v{version}/{route...}`
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let (version, route) = match segments.as_slice() {
+        ["api", version, route @ ..] if !route.is_empty() => (*version, route),
+        _ => return status(StatusCode::NOT_FOUND),
+    };
+    let bucket = bucket_of(route);
+
+    let mut state = State::lock(state);
+    *state.requests.entry(bucket).or_default() += 1;
+
+    if !state.failures.is_empty() {
+        let failure = state.failures.remove(0);
+        return status(failure);
+    }
+
+    let mut ratelimit_headers = None;
+    if let Some(ratelimit) = state.ratelimits.get_mut(&bucket) {
+        let now = Utc::now();
+        if now >= ratelimit.resets_at {
+            ratelimit.remaining = ratelimit.limit;
+            ratelimit.resets_at = now + ratelimit.window;
+        }
+
+        let reset = ratelimit
+            .resets_at
+            .to_rfc3339_opts(SecondsFormat::Millis, true);
+        if ratelimit.remaining == 0 {
+            let retry_after = (ratelimit.resets_at - now).num_seconds().max(0) + 1;
+            return Response::builder()
+                .status(StatusCode::TOO_MANY_REQUESTS)
+                .header("X-Rate-Limit-Limit", ratelimit.limit)
+                .header("X-Rate-Limit-Remaining", 0)
+                .header("X-Rate-Limit-Reset", reset)
+                .header(hyper::header::RETRY_AFTER, retry_after)
+                .body(Body::empty())
+                .unwrap();
+        }
+
+        ratelimit.remaining -= 1;
+        ratelimit_headers = Some((ratelimit.limit, ratelimit.remaining, reset));
+    }
+
+    let base = format!("http://{host}/api/{version}");
+    let mut resp = match route {
+        ["endpoints"] => json_response(endpoints()),
+        ["metrics"] => json_response(metrics()),
+        ["search"] => search(&mut state, &base, &query),
+        [category] => match category.parse::<Category>() {
+            Ok(category) => {
+                let amount = amount(&query);
+                json_response(json!({
+                    "results": results(&mut state, &base, category, amount, None),
+                }))
+            }
+            Err(_) => status(StatusCode::NOT_FOUND),
+        },
+        [category, file] => asset(category, file),
+        _ => status(StatusCode::NOT_FOUND),
+    };
+
+    if let Some((limit, remaining, reset)) = ratelimit_headers {
+        let headers = resp.headers_mut();
+        headers.insert("X-Rate-Limit-Limit", limit.into());
+        headers.insert("X-Rate-Limit-Remaining", remaining.into());
+        headers.insert("X-Rate-Limit-Reset", reset.parse().unwrap());
+    }
+
+    resp
+}
+
+fn status(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn json_response(value: Value) -> Response<Body> {
+    Response::builder()
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(value.to_string()))
+        .unwrap()
+}
+
+/// The `amount` query parameter, clamped to 1..=20 like the real server does.
+fn amount(query: &HashMap<String, String>) -> usize {
+    query
+        .get("amount")
+        .and_then(|amount| amount.parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, 20)
+}
+
+fn results(
+    state: &mut State,
+    base: &str,
+    category: Category,
+    amount: usize,
+    query: Option<&str>,
+) -> Vec<Value> {
+    (0..amount)
+        .map(|_| {
+            state.next_id += 1;
+            let id = format!("00000000-0000-4000-8000-{:012x}", state.next_id);
+            if is_image(category) {
+                json!({
+                    "url": format!("{base}/{category}/{id}.png"),
+                    "artist_href": FIXTURE_ARTIST_HREF,
+                    "artist_name": query.unwrap_or(FIXTURE_ARTIST_NAME),
+                    "source_url": FIXTURE_SOURCE_URL,
+                })
+            } else {
+                json!({
+                    "url": format!("{base}/{category}/{id}.gif"),
+                    "anime_name": query.unwrap_or(FIXTURE_ANIME_NAME),
+                })
+            }
+        })
+        .collect()
+}
+
+fn search(state: &mut State, base: &str, query: &HashMap<String, String>) -> Response<Body> {
+    let (Some(text), Some(kind)) = (query.get("query"), query.get("type")) else {
+        return status(StatusCode::BAD_REQUEST);
+    };
+
+    let category = match (query.get("category"), kind.as_str()) {
+        (Some(category), _) => match category.parse::<Category>() {
+            Ok(category) => category,
+            Err(_) => return status(StatusCode::BAD_REQUEST),
+        },
+        (None, "1") => Category::Neko,
+        (None, "2") => Category::Hug,
+        _ => return status(StatusCode::BAD_REQUEST),
+    };
+
+    let amount = amount(query);
+    json_response(json!({
+        "results": results(state, base, category, amount, Some(text)),
+    }))
+}
+
+fn endpoints() -> Value {
+    Category::ALL_VARIANTS
+        .iter()
+        .map(|category| {
+            (category.to_string(), json!({ "format": category.format() }))
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+fn metrics() -> Value {
+    let counts = Category::ALL_VARIANTS
+        .iter()
+        .map(|category| (category.to_string(), json!("1")))
+        .chain([("total".to_owned(), json!(Category::ALL_VARIANTS.len().to_string()))])
+        .collect::<serde_json::Map<_, _>>();
+
+    json!({
+        "per_id": counts,
+        "per_random": counts,
+        "total": (2 * Category::ALL_VARIANTS.len()).to_string(),
+        "reset_in": "1h 2m 3s",
+    })
+}
+
+fn asset(category: &str, file: &str) -> Response<Body> {
+    let Ok(category) = category.parse::<Category>() else {
+        return status(StatusCode::NOT_FOUND);
+    };
+
+    let builder = Response::builder();
+    let builder = match (is_image(category), file.rsplit_once('.')) {
+        (true, Some((_, "png"))) => builder
+            .header(hyper::header::CONTENT_TYPE, "image/png")
+            .header("artist_name", urlencoding::encode(FIXTURE_ARTIST_NAME).as_ref())
+            .header("artist_href", urlencoding::encode(FIXTURE_ARTIST_HREF).as_ref())
+            .header("source_url", urlencoding::encode(FIXTURE_SOURCE_URL).as_ref()),
+        (false, Some((_, "gif"))) => builder
+            .header(hyper::header::CONTENT_TYPE, "image/gif")
+            .header("anime_name", urlencoding::encode(FIXTURE_ANIME_NAME).as_ref()),
+        _ => return status(StatusCode::NOT_FOUND),
+    };
+
+    let body = if is_image(category) {
+        FIXTURE_PNG
+    } else {
+        FIXTURE_GIF
+    };
+
+    builder.body(Body::from(body)).unwrap()
+}
@@ -0,0 +1,280 @@
+//! Recording and replaying of the HTTP interactions of a [`Client`](crate::client::Client),
+//! VCR-style, so tests can run deterministically without network access.
+//!
+//! A [`Cassette`] in [`CassetteMode::Record`] mode lets requests through and
+//! records each of them, with the full response (status, headers and body).
+//! In [`CassetteMode::Replay`] mode, no request is sent, and the recorded
+//! responses are served instead, matching them by method and url, in the order
+//! they were recorded. The rate limits are not enforced while replaying.
+//!
+//! ```rust ,no_run
+//! # use nekosbest::client::{Client, ClientConfig};
+//! # use nekosbest::vcr::Cassette;
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), nekosbest::NekosBestError> {
+//! // replays `tests/cassettes/neko.json` if it exists, records it otherwise
+//! let cassette = Cassette::replay_or_record("tests/cassettes/neko.json")?;
+//! let client = Client::new(ClientConfig::default()).with_cassette(cassette.clone());
+//!
+//! let neko = client.get(nekosbest::Category::Neko).await?;
+//! let details = client.details(&neko.url).await?;
+//!
+//! // no-op when replaying
+//! cassette.save()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use base64::Engine;
+#[cfg(feature = "blocking")]
+use nb_blocking_util::blocking;
+use reqwest::{Method, ResponseBuilderExt, Url};
+
+use crate::client::ReqwestResponse;
+use crate::NekosBestError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CassetteError {
+    #[error("no recorded response for {method} {url}")]
+    NoMatch { method: Method, url: Url },
+    #[error("invalid recorded response")]
+    InvalidResponse(#[from] http::Error),
+    #[error("invalid recorded body")]
+    InvalidBody(#[from] base64::DecodeError),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send the requests, and record them.
+    Record,
+    /// Serve the recorded responses, without sending anything.
+    Replay,
+}
+
+/// A request, and the response it got.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, RecordedBytes)>,
+    pub body: RecordedBytes,
+}
+
+/// The body or a header value of a response, as text if it is valid UTF-8,
+/// base64 encoded otherwise.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedBytes {
+    Text(String),
+    Base64(String),
+}
+
+impl RecordedBytes {
+    fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => RecordedBytes::Text(text.to_owned()),
+            Err(_) => RecordedBytes::Base64(base64::engine::general_purpose::STANDARD.encode(bytes)),
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, CassetteError> {
+        match self {
+            RecordedBytes::Text(text) => Ok(text.clone().into_bytes()),
+            RecordedBytes::Base64(data) => Ok(base64::engine::general_purpose::STANDARD.decode(data)?),
+        }
+    }
+}
+
+struct State {
+    interactions: Vec<Interaction>,
+    replayed: Vec<bool>,
+}
+
+struct Inner {
+    path: PathBuf,
+    mode: CassetteMode,
+    state: Mutex<State>,
+}
+
+/// A set of recorded interactions, backed by a JSON file.
+///
+/// Cloning a cassette is cheap, and the clones share the recorded interactions.
+#[derive(Clone)]
+pub struct Cassette {
+    inner: Arc<Inner>,
+}
+
+impl Cassette {
+    fn new(path: PathBuf, mode: CassetteMode, interactions: Vec<Interaction>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                path,
+                mode,
+                state: Mutex::new(State {
+                    replayed: vec![false; interactions.len()],
+                    interactions,
+                }),
+            }),
+        }
+    }
+
+    /// A new, empty cassette, recording to `path` when [saved](Cassette::save).
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::new(path.into(), CassetteMode::Record, Vec::new())
+    }
+
+    /// Loads the cassette at `path`, to replay it.
+    ///
+    /// # Errors
+    /// If the file cannot be read or is not a valid cassette.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, NekosBestError> {
+        let path = path.into();
+        let interactions = serde_json::from_slice(&std::fs::read(&path)?)?;
+
+        Ok(Self::new(path, CassetteMode::Replay, interactions))
+    }
+
+    /// Replays the cassette at `path` if it exists, records a new one otherwise.
+    ///
+    /// # Errors
+    /// If the file exists, but cannot be read or is not a valid cassette.
+    pub fn replay_or_record(path: impl Into<PathBuf>) -> Result<Self, NekosBestError> {
+        let path = path.into();
+        if path.exists() {
+            Self::replay(path)
+        } else {
+            Ok(Self::record(path))
+        }
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.inner.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// The interactions recorded so far, or loaded from the file.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state().interactions.clone()
+    }
+
+    /// Writes the recorded interactions to the file of the cassette,
+    /// creating the missing parent directories. Does nothing when replaying.
+    ///
+    /// # Errors
+    /// If the file cannot be written.
+    pub fn save(&self) -> Result<(), NekosBestError> {
+        if self.mode() == CassetteMode::Replay {
+            return Ok(());
+        }
+
+        if let Some(parent) = self.path().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(&self.state().interactions)?;
+        std::fs::write(self.path(), json)?;
+
+        Ok(())
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Serves the first recorded response to `method url` that was not served yet.
+    pub(crate) fn replay_response(
+        &self,
+        method: &Method,
+        url: &Url,
+    ) -> Result<ReqwestResponse, NekosBestError> {
+        let mut state = self.state();
+        let State {
+            interactions,
+            replayed,
+        } = &mut *state;
+
+        let (interaction, replayed) = interactions
+            .iter()
+            .zip(replayed.iter_mut())
+            .find(|(interaction, replayed)| {
+                !**replayed
+                    && interaction.request.method == method.as_str()
+                    && interaction.request.url == url.as_str()
+            })
+            .ok_or_else(|| CassetteError::NoMatch {
+                method: method.clone(),
+                url: url.clone(),
+            })?;
+        *replayed = true;
+
+        let recorded = &interaction.response;
+        let mut builder = http::Response::builder()
+            .status(recorded.status)
+            .url(url.clone());
+        for (name, value) in &recorded.headers {
+            builder = builder.header(name, value.to_bytes()?);
+        }
+        let resp = builder
+            .body(recorded.body.to_bytes()?)
+            .map_err(CassetteError::from)?;
+
+        Ok(ReqwestResponse::from(resp))
+    }
+
+    /// Records `resp`, the response to `method url`, returning an equivalent
+    /// response, since the body has to be consumed to be recorded.
+    #[cfg_attr(feature = "blocking", blocking)]
+    pub(crate) async fn record_response(
+        &self,
+        method: &Method,
+        url: &Url,
+        resp: ReqwestResponse,
+    ) -> Result<ReqwestResponse, NekosBestError> {
+        let status = resp.status();
+        let final_url = resp.url().clone();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+
+        self.state().interactions.push(Interaction {
+            request: RecordedRequest {
+                method: method.to_string(),
+                url: url.to_string(),
+            },
+            response: RecordedResponse {
+                status: status.as_u16(),
+                headers: headers
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), RecordedBytes::new(value.as_bytes())))
+                    .collect(),
+                body: RecordedBytes::new(&body),
+            },
+        });
+
+        let mut builder = http::Response::builder().status(status).url(final_url);
+        if let Some(h) = builder.headers_mut() {
+            *h = headers;
+        }
+        let resp = builder.body(body.to_vec()).map_err(CassetteError::from)?;
+
+        Ok(ReqwestResponse::from(resp))
+    }
+}
@@ -0,0 +1,372 @@
+use std::time::Duration;
+
+use nekosbest::client::{Client, ClientConfig, RatelimitBehavior, RatelimitBucket, RetryPolicy};
+use nekosbest::reqwest::StatusCode;
+use nekosbest::testing::{self, FakeServer};
+use nekosbest::{Category, NekosBestError, SearchQuery, SearchQueryKind};
+
+#[tokio::test]
+async fn get_and_details() {
+    let server = FakeServer::start();
+    let client = server.client();
+
+    let neko = client.get(Category::Neko).await.unwrap();
+    assert!(neko.url.starts_with(&server.api_root()));
+    assert!(neko.url.ends_with(".png"));
+    let image = neko.details.try_into_image().unwrap();
+    assert_eq!(image.artist_name, testing::FIXTURE_ARTIST_NAME);
+
+    let hugs = client.get_many(Category::Hug, 5).await.unwrap();
+    assert_eq!(hugs.len(), 5);
+    let hug = &hugs[0];
+    assert!(hug.url.ends_with(".gif"));
+
+    let gif = client.details(&hug.url).await.unwrap().try_into_gif().unwrap();
+    assert_eq!(gif.anime_name, testing::FIXTURE_ANIME_NAME);
+
+    let image = client.details(&neko.url).await.unwrap().try_into_image().unwrap();
+    assert_eq!(image.artist_href.as_str(), testing::FIXTURE_ARTIST_HREF);
+    assert_eq!(image.source_url.as_str(), testing::FIXTURE_SOURCE_URL);
+}
+
+#[tokio::test]
+async fn search() {
+    let server = FakeServer::start();
+    let client = server.client();
+
+    let resp = client
+        .search(
+            SearchQuery::new("Senko", SearchQueryKind::Gif)
+                .category(Category::Pat)
+                .amount(3),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.len(), 3);
+    for r in resp.iter() {
+        assert!(r.url.contains("/pat/"));
+        assert_eq!(r.details.clone().try_into_gif().unwrap().anime_name, "Senko");
+    }
+    assert_eq!(server.requests(RatelimitBucket::Search), 1);
+}
+
+#[tokio::test]
+async fn retries_server_errors() {
+    let server = FakeServer::start();
+    let client = server.client();
+
+    server.fail_next([StatusCode::SERVICE_UNAVAILABLE]);
+    client.get(Category::Pat).await.unwrap();
+    assert_eq!(server.requests(RatelimitBucket::Category), 2);
+
+    server.fail_next([StatusCode::SERVICE_UNAVAILABLE]);
+    let err = client
+        .with_retry_policy(RetryPolicy::disabled())
+        .get(Category::Pat)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        NekosBestError::ReqwestError(e) if e.status() == Some(StatusCode::SERVICE_UNAVAILABLE)
+    ));
+}
+
+#[tokio::test]
+async fn ratelimits() {
+    let server = FakeServer::start();
+    let client = Client::new(ClientConfig {
+        category_ratelimit_behavior: RatelimitBehavior::Error,
+        ..server.client_config()
+    });
+
+    server.set_ratelimit(RatelimitBucket::Category, 2, Duration::from_secs(60));
+    client.get(Category::Neko).await.unwrap();
+    client.get(Category::Neko).await.unwrap();
+    assert!(matches!(
+        client.get(Category::Neko).await,
+        Err(NekosBestError::RateLimited)
+    ));
+    // the limit was enforced locally, without hitting the server
+    assert_eq!(server.requests(RatelimitBucket::Category), 2);
+
+    // other buckets are not affected
+    client
+        .search(SearchQuery::new("Senko", SearchQueryKind::Gif))
+        .await
+        .unwrap();
+}
+
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn metrics() {
+    use nekosbest::metrics::CategoryOrTotal;
+
+    let server = FakeServer::start();
+    let metrics = server.client().metrics().await.unwrap();
+
+    assert_eq!(metrics.per_id[&CategoryOrTotal::Category(Category::Neko)], 1);
+    assert_eq!(metrics.total, 2 * Category::ALL_VARIANTS.len());
+}
+
+#[cfg(feature = "download")]
+#[tokio::test]
+async fn download() {
+    use nekosbest::download::DownloadResult;
+
+    let server = FakeServer::start();
+    let client = server.client();
+
+    let neko = client.get(Category::Neko).await.unwrap();
+    match client.download(&neko).await.unwrap() {
+        DownloadResult::Image(image) => assert_eq!((image.width(), image.height()), (2, 2)),
+        DownloadResult::Gif(_) => panic!("expected an image"),
+    }
+
+    let hug = client.get(Category::Hug).await.unwrap();
+    match client.download(&hug).await.unwrap() {
+        DownloadResult::Gif(gif) => assert_eq!(gif.get_frames().len(), 2),
+        DownloadResult::Image(_) => panic!("expected a gif"),
+    }
+
+    assert_eq!(server.requests(RatelimitBucket::Assets), 2);
+}
+
+#[tokio::test]
+async fn endpoints() {
+    let server = FakeServer::start();
+    let endpoints = server.client().endpoints().await.unwrap();
+
+    assert_eq!(endpoints.len(), Category::ALL_VARIANTS.len());
+    assert!(endpoints.drift().is_empty());
+}
+
+#[tokio::test]
+async fn get_any() {
+    use nekosbest::CategorySet;
+
+    let server = FakeServer::start();
+    let client = server.client();
+
+    let set = CategorySet::from_slice(&[Category::Hug, Category::Cuddle]);
+    let chosen = client.get_any(set).await.unwrap();
+    assert!(set.contains(chosen.category));
+    assert!(chosen
+        .response
+        .url
+        .contains(&format!("/{}/", chosen.category)));
+
+    let chosen = client
+        .get_weighted(&[(Category::Neko, 1), (Category::Slap, 0)])
+        .await
+        .unwrap();
+    assert_eq!(chosen.category, Category::Neko);
+
+    assert!(matches!(
+        client.get_any(CategorySet::EMPTY).await,
+        Err(NekosBestError::EmptyChoice)
+    ));
+}
+
+#[tokio::test]
+async fn bulk() {
+    use nekosbest::Amount;
+    use std::collections::HashSet;
+
+    let server = FakeServer::start();
+    let client = server.client();
+
+    let bulk = client.get_bulk(Category::Pat, 45).await.unwrap();
+    assert!(bulk.is_complete());
+    assert!(bulk.error.is_none());
+    let urls = bulk.results.iter().map(|r| &r.url).collect::<HashSet<_>>();
+    assert_eq!(urls.len(), 45);
+    assert_eq!(server.requests(RatelimitBucket::Category), 3);
+
+    assert!(matches!(
+        client.get_bulk(Category::Pat, usize::MAX).await,
+        Err(NekosBestError::BulkTooLarge(usize::MAX))
+    ));
+    assert!(matches!(
+        client.get_many(Category::Pat, 0).await,
+        Err(NekosBestError::InvalidAmount(_))
+    ));
+    assert_eq!(server.requests(RatelimitBucket::Category), 3);
+
+    // a failed page stops the requests of the pages left: of the 10 pages,
+    // only the ones already in flight were requested
+    server.fail_next([StatusCode::BAD_REQUEST]);
+    match client.get_bulk(Category::Pat, 200).await {
+        Ok(bulk) => {
+            assert!(!bulk.is_complete());
+            assert!(bulk.error.is_some());
+        }
+        Err(e) => assert!(matches!(e, NekosBestError::ReqwestError(_))),
+    }
+    let sent = server.requests(RatelimitBucket::Category) - 3;
+    assert!((1..=4).contains(&sent), "{sent} requests");
+
+    client
+        .get_many(Category::Pat, Amount::new(20).unwrap())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn stream() {
+    use futures::StreamExt;
+    use std::collections::HashSet;
+
+    let server = FakeServer::start();
+    let client = server.client();
+
+    let urls = client
+        .stream(Category::Wave)
+        .take(50)
+        .map(|r| r.unwrap().url)
+        .collect::<HashSet<_>>()
+        .await;
+    assert_eq!(urls.len(), 50);
+    assert_eq!(server.requests(RatelimitBucket::Category), 3);
+
+    // transient errors are yielded, and the stream goes on
+    let no_retries = client.with_retry_policy(RetryPolicy {
+        max_attempts: 1,
+        initial_backoff: Duration::from_millis(1),
+        ..RetryPolicy::default()
+    });
+    server.fail_next([StatusCode::SERVICE_UNAVAILABLE]);
+    let mut stream = no_retries.stream(Category::Wave);
+    assert!(stream.next().await.unwrap().is_err());
+    assert!(stream.next().await.unwrap().is_ok());
+
+    // until there are too many of them in a row
+    server.fail_next([StatusCode::SERVICE_UNAVAILABLE; 3]);
+    let errors = no_retries.stream(Category::Wave).collect::<Vec<_>>().await;
+    assert_eq!(errors.len(), 3);
+    assert!(errors.iter().all(Result::is_err));
+
+    // other errors end the stream
+    server.fail_next([StatusCode::BAD_REQUEST]);
+    let mut stream = client.stream(Category::Wave);
+    assert!(stream.next().await.unwrap().is_err());
+    assert!(stream.next().await.is_none());
+
+    let mut stream = client.stream("no-such-category");
+    assert!(stream.next().await.unwrap().is_err());
+    assert!(stream.next().await.is_none());
+
+    #[cfg(feature = "strong-types")]
+    {
+        let mut stream = client.stream_typed::<nekosbest::Wave>();
+        let wave = stream.next().await.unwrap().unwrap();
+        assert!(!wave.details.anime_name.is_empty());
+    }
+}
+
+#[cfg(feature = "prefetch")]
+#[tokio::test]
+async fn prefetch() {
+    use nekosbest::prefetch::PrefetchPool;
+    use nekosbest::CategorySet;
+
+    async fn until(mut cond: impl FnMut() -> bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !cond() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    let server = FakeServer::start();
+    let client = server.client();
+
+    let pool = PrefetchPool::builder(&client)
+        .categories(CategorySet::from(Category::Hug))
+        .watermarks(2, 5)
+        .spawn();
+    until(|| pool.buffered(Category::Hug) == 5).await;
+    assert_eq!(server.requests(RatelimitBucket::Category), 1);
+
+    for _ in 0..3 {
+        assert!(pool.try_take(Category::Hug).is_some());
+    }
+    until(|| pool.buffered(Category::Hug) == 5).await;
+    assert_eq!(server.requests(RatelimitBucket::Category), 2);
+
+    // categories outside of the pool are fetched directly
+    assert!(pool.try_take(Category::Pat).is_none());
+    pool.take(Category::Pat).await.unwrap();
+    assert_eq!(server.requests(RatelimitBucket::Category), 3);
+
+    // the next refill uses up the rate limit, and the one after
+    // waits for it to reset, which doesn't hold up the shutdown
+    server.set_ratelimit(RatelimitBucket::Category, 1, Duration::from_secs(3600));
+    for _ in 0..3 {
+        assert!(pool.try_take(Category::Hug).is_some());
+    }
+    until(|| pool.buffered(Category::Hug) == 5).await;
+    for _ in 0..3 {
+        assert!(pool.try_take(Category::Hug).is_some());
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(server.requests(RatelimitBucket::Category), 4);
+
+    let handle = pool.shutdown_handle();
+    tokio::time::timeout(Duration::from_secs(5), pool.shutdown())
+        .await
+        .unwrap();
+    assert!(handle.is_shutdown());
+}
+
+#[tokio::test]
+async fn cache() {
+    use nekosbest::cache::{CacheConfig, CacheRoute, CacheStats};
+
+    let server = FakeServer::start();
+    let client = server.client().with_cache(CacheConfig::default());
+
+    let neko = client.get(Category::Neko).await.unwrap();
+    client.details(&neko.url).await.unwrap();
+    client.image_details(&neko.url).await.unwrap();
+    assert!(client.gif_details(&neko.url).await.is_err());
+    assert_eq!(server.requests(RatelimitBucket::Assets), 1);
+
+    let query = SearchQuery::new("Senko", SearchQueryKind::Gif).amount(3);
+    client.search(query.clone()).await.unwrap();
+    client.search(query.clone()).await.unwrap();
+    client.search(query.amount(4)).await.unwrap();
+    assert_eq!(server.requests(RatelimitBucket::Search), 2);
+
+    client.endpoints().await.unwrap();
+    client.endpoints().await.unwrap();
+
+    let cache = client.cache().unwrap();
+    assert_eq!(cache.stats(CacheRoute::Details), CacheStats { hits: 2, misses: 1 });
+    assert_eq!(cache.stats(CacheRoute::Search), CacheStats { hits: 1, misses: 2 });
+    assert_eq!(cache.stats(CacheRoute::Endpoints), CacheStats { hits: 1, misses: 1 });
+    assert_eq!(cache.total_stats(), CacheStats { hits: 4, misses: 4 });
+    assert_eq!(cache.len(), 4);
+
+    // a client without a cache always sends the requests
+    server.client().details(&neko.url).await.unwrap();
+    assert_eq!(server.requests(RatelimitBucket::Assets), 2);
+
+    // typed searches are cached per category, apart from the untyped ones
+    #[cfg(feature = "strong-types")]
+    {
+        use nekosbest::{STNekosBestSearchQuery, Wave};
+
+        let query = || STNekosBestSearchQuery::<Wave>::new("Senko").amount(3);
+        let wave = client.search_typed(query()).await.unwrap();
+        assert!(!wave[0].details.anime_name.is_empty());
+        client.search_typed(query()).await.unwrap();
+        client
+            .search_typed(STNekosBestSearchQuery::<nekosbest::Hug>::new("Senko").amount(3))
+            .await
+            .unwrap();
+        assert_eq!(server.requests(RatelimitBucket::Search), 4);
+        assert_eq!(cache.stats(CacheRoute::Search), CacheStats { hits: 2, misses: 4 });
+    }
+}
@@ -0,0 +1,119 @@
+use nekosbest::api::NekosBestApi;
+use nekosbest::details::{Details, GifDetails};
+use nekosbest::mock::{Call, MockApi};
+use nekosbest::{AnyCategory, Category, NekosBestError, NekosBestResponseSingle};
+
+/// A command handler, as it would be written in a bot.
+async fn hug_command(api: &dyn NekosBestApi, target: &str) -> String {
+    match api.get(Category::Hug.into()).await {
+        Ok(resp) => format!("*hugs {target}* {}", resp.url),
+        Err(_) => "no hugs today".to_owned(),
+    }
+}
+
+#[tokio::test]
+async fn handler_with_mock() {
+    let api = MockApi::new();
+    api.push_get(Ok(NekosBestResponseSingle {
+        url: "https://nekos.best/api/v2/hug/1.gif".to_owned(),
+        details: Details::Gif(GifDetails {
+            anime_name: "Senko-san".to_owned(),
+        }),
+    }))
+    .push_get(Err(NekosBestError::RateLimited));
+
+    assert_eq!(
+        hug_command(&api, "Senko").await,
+        "*hugs Senko* https://nekos.best/api/v2/hug/1.gif"
+    );
+    assert_eq!(hug_command(&api, "Senko").await, "no hugs today");
+    // nothing queued anymore
+    assert!(matches!(
+        api.get(Category::Pat.into()).await,
+        Err(NekosBestError::NotFound)
+    ));
+
+    assert_eq!(
+        api.calls(),
+        vec![
+            Call::Get(Category::Hug.into()),
+            Call::Get(Category::Hug.into()),
+            Call::Get("pat".into())
+        ]
+    );
+}
+
+fn hug(id: u32) -> NekosBestResponseSingle {
+    NekosBestResponseSingle {
+        url: format!("https://nekos.best/api/v2/hug/{id}.gif"),
+        details: Details::Gif(GifDetails {
+            anime_name: "Senko-san".to_owned(),
+        }),
+    }
+}
+
+#[tokio::test]
+async fn history_with_mock() {
+    use nekosbest::history::{History, HistoryStore, MemoryHistoryStore};
+    use nekosbest::NekosBestResponse;
+
+    let history = History::new(MockApi::new(), MemoryHistoryStore::new(), 2).max_attempts(2);
+    let api = history.api();
+
+    api.push_get(Ok(hug(1)));
+    assert_eq!(history.get("channel:1", Category::Hug).await.unwrap().url, hug(1).url);
+
+    // a repeat is fetched again, from a whole page
+    api.push_get(Ok(hug(1)))
+        .push_get_amount(Ok(NekosBestResponse(vec![hug(1), hug(2)])));
+    assert_eq!(history.get("channel:1", Category::Hug).await.unwrap().url, hug(2).url);
+
+    // other scopes have their own history
+    api.push_get(Ok(hug(1)));
+    assert_eq!(history.get("channel:2", Category::Hug).await.unwrap().url, hug(1).url);
+
+    api.push_get(Ok(hug(2)))
+        .push_get_amount(Ok(NekosBestResponse(vec![hug(1), hug(2)])));
+    assert!(matches!(
+        history.get("channel:1", Category::Hug).await,
+        Err(NekosBestError::NoFreshImage)
+    ));
+
+    // only the last 2 urls are kept, and unknown categories can be used
+    api.push_get(Ok(hug(3)));
+    api.clear_calls();
+    history.get("channel:1", "brand-new").await.unwrap();
+    assert_eq!(api.calls(), [Call::Get(AnyCategory::Other("brand-new".to_owned()))]);
+    assert_eq!(
+        history.store().recent("channel:1").unwrap(),
+        [hug(2).url, hug(3).url]
+    );
+}
+
+#[test]
+fn file_history_store() {
+    use nekosbest::history::{FileHistoryStore, HistoryStore};
+
+    let path = std::env::temp_dir()
+        .join(format!("nekosbest-history-{}", std::process::id()))
+        .join("history.json");
+
+    let store = FileHistoryStore::open(&path).unwrap();
+    assert!(store.recent("user:1").unwrap().is_empty());
+    assert!(store.record_if_absent("user:1", "a", 2).unwrap());
+    assert!(store.record_if_absent("user:1", "b", 2).unwrap());
+    assert!(!store.record_if_absent("user:1", "b", 2).unwrap());
+    assert!(store.record_if_absent("user:1", "c", 2).unwrap());
+    drop(store);
+
+    let store = FileHistoryStore::open(&path).unwrap();
+    assert_eq!(store.recent("user:1").unwrap(), ["b", "c"]);
+
+    // a failed write leaves the history as it was, here because the
+    // temporary file `history.json.tmp` cannot be created
+    std::fs::create_dir(path.with_extension("json.tmp")).unwrap();
+    assert!(store.record_if_absent("user:1", "d", 2).is_err());
+    assert_eq!(store.recent("user:1").unwrap(), ["b", "c"]);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
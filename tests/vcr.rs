@@ -0,0 +1,88 @@
+use nekosbest::client::Client;
+use nekosbest::testing::FakeServer;
+use nekosbest::vcr::{Cassette, CassetteError, CassetteMode, RecordedBytes};
+use nekosbest::{Category, NekosBestError};
+
+#[tokio::test]
+async fn record_then_replay() {
+    let path = std::env::temp_dir()
+        .join(format!("nekosbest-vcr-{}", std::process::id()))
+        .join("cassette.json");
+
+    let server = FakeServer::start();
+    let config = server.client_config();
+
+    let cassette = Cassette::replay_or_record(&path).unwrap();
+    assert_eq!(cassette.mode(), CassetteMode::Record);
+    let client = server.client().with_cassette(cassette.clone());
+    let recorded_neko = client.get(Category::Neko).await.unwrap();
+    let recorded_details = client.details(&recorded_neko.url).await.unwrap();
+    cassette.save().unwrap();
+
+    let interactions = cassette.interactions();
+    assert_eq!(interactions.len(), 2);
+    assert!(matches!(interactions[0].response.body, RecordedBytes::Text(_)));
+    // the fixture png
+    assert!(matches!(interactions[1].response.body, RecordedBytes::Base64(_)));
+    assert!(interactions[1]
+        .response
+        .headers
+        .iter()
+        .any(|(name, _)| name == "artist_name"));
+
+    // no server to talk to anymore
+    drop(server);
+
+    let cassette = Cassette::replay_or_record(&path).unwrap();
+    assert_eq!(cassette.mode(), CassetteMode::Replay);
+    let client = Client::new(config).with_cassette(cassette);
+    let neko = client.get(Category::Neko).await.unwrap();
+    assert_eq!(neko.url, recorded_neko.url);
+    assert_eq!(
+        neko.details.try_into_image().unwrap(),
+        recorded_details.try_into_image().unwrap()
+    );
+    client.details(&neko.url).await.unwrap();
+
+    // every interaction is replayed only once
+    assert!(matches!(
+        client.get(Category::Neko).await,
+        Err(NekosBestError::Cassette(CassetteError::NoMatch { .. }))
+    ));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn replay_redirected() {
+    let path = std::env::temp_dir()
+        .join(format!("nekosbest-vcr-redirect-{}", std::process::id()))
+        .join("cassette.json");
+
+    let server = FakeServer::start();
+    server.redirect("/api/v2/neko", "/api/v2/kitsune");
+    let config = server.client_config();
+
+    let cassette = Cassette::record(&path);
+    let client = server.client().with_cassette(cassette.clone());
+    let recorded = client.get(Category::Neko).await.unwrap();
+    assert!(recorded.url.contains("/kitsune/"));
+    cassette.save().unwrap();
+    // recorded under the url that was requested
+    assert!(cassette.interactions()[0].request.url.contains("/neko"));
+    drop(server);
+
+    let cassette = Cassette::replay(&path).unwrap();
+    let client = Client::new(config).with_cassette(cassette);
+    assert_eq!(client.get(Category::Neko).await.unwrap().url, recorded.url);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn replay_missing_cassette() {
+    assert!(matches!(
+        Cassette::replay("/nonexistent/cassette.json"),
+        Err(NekosBestError::IO(_))
+    ));
+}